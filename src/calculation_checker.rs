@@ -0,0 +1,196 @@
+// calculation_checker.rs
+use std::collections::HashMap;
+
+use crate::models::xbrl::{Decimals, DynamicXBRLContent};
+use crate::models::linkbase::{CalculationInconsistency, Network};
+use crate::statement_builder::is_consolidated_role;
+
+const SUMMATION_ITEM_ARCROLE: &str = "http://www.xbrl.org/2003/arcrole/summation-item";
+
+/// A reported numeric value with the unit and precision it was reported in.
+struct ReportedValue<'a> {
+    unit_ref: &'a str,
+    value: f64,
+    decimals: Option<Decimals>,
+}
+
+impl ReportedValue<'_> {
+    /// Half a unit of the last reported digit: the value may be off by this
+    /// much from the figure it was rounded from.
+    fn rounding_error(&self) -> f64 {
+        match self.decimals {
+            Some(Decimals::Finite(d)) => 10f64.powi(-d) / 2.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Evaluates every summation-item arc of the calculation linkbase against
+/// the reported facts. A total is checked in each context where it is
+/// reported together with at least one of its items in the same unit.
+/// Following XBRL Calculations 1.1, every fact stands for the range of
+/// values that round to it at its `decimals`, so a summation only fails when
+/// the total's range cannot meet the range of the weighted sum, and totals
+/// of line items that were each rounded to millions are not flagged.
+/// Consolidated roles are checked in dimensionless contexts,
+/// non-consolidated roles in non-consolidated ones.
+pub fn check_calculations(xbrl: &DynamicXBRLContent, calculation: &[Network]) -> Vec<CalculationInconsistency> {
+    let mut reported: HashMap<(&str, &str), ReportedValue> = HashMap::new();
+    for fact in xbrl.facts() {
+        let (Some(context_ref), Some(unit_ref)) = (fact.context_ref.as_deref(), fact.unit_ref.as_deref()) else {
+            continue;
+        };
+        let Some(numeric) = fact.numeric_value() else {
+            continue;
+        };
+        if let Some(value) = numeric.value {
+            reported.entry((fact.name.as_str(), context_ref)).or_insert(ReportedValue {
+                unit_ref,
+                value,
+                decimals: numeric.decimals,
+            });
+        }
+    }
+
+    let mut context_refs: Vec<&str> = xbrl.contexts.keys().map(String::as_str).collect();
+    context_refs.sort();
+
+    let mut inconsistencies = Vec::new();
+
+    for network in calculation {
+        let consolidated = is_consolidated_role(&network.role);
+
+        let mut totals: Vec<&str> = Vec::new();
+        for r in network.relationships.iter().filter(|r| r.arcrole == SUMMATION_ITEM_ARCROLE) {
+            if !totals.contains(&r.from.as_str()) {
+                totals.push(&r.from);
+            }
+        }
+
+        for total in totals {
+            let items: Vec<_> = network.children(total)
+                .into_iter()
+                .filter(|r| r.arcrole == SUMMATION_ITEM_ARCROLE)
+                .collect();
+
+            for &context_ref in &context_refs {
+                if !xbrl.contexts.get(context_ref).is_some_and(|c| c.is_statement_context(consolidated)) {
+                    continue;
+                }
+                let Some(parent) = reported.get(&(total, context_ref)) else {
+                    continue;
+                };
+
+                let contributions: Vec<(f64, &ReportedValue)> = items.iter()
+                    .filter_map(|r| {
+                        let item = reported.get(&(r.to.as_str(), context_ref))?;
+                        (item.unit_ref == parent.unit_ref).then_some((r.weight.unwrap_or(1.0), item))
+                    })
+                    .collect();
+                if contributions.is_empty() {
+                    continue;
+                }
+
+                let computed: f64 = contributions.iter()
+                    .map(|(weight, item)| weight * item.value)
+                    .sum();
+                let tolerance = parent.rounding_error() + contributions.iter()
+                    .map(|(weight, item)| weight.abs() * item.rounding_error())
+                    .sum::<f64>();
+                // Float noise on exact values.
+                let tolerance = tolerance.max(1e-6);
+
+                if (parent.value - computed).abs() > tolerance {
+                    inconsistencies.push(CalculationInconsistency {
+                        role: network.role.clone(),
+                        concept: total.to_string(),
+                        context_ref: context_ref.to_string(),
+                        reported: parent.value,
+                        computed,
+                        decimals: std::iter::once(parent.decimals)
+                            .chain(contributions.iter().map(|(_, item)| item.decimals))
+                            .filter_map(|d| match d {
+                                Some(Decimals::Finite(d)) => Some(d),
+                                _ => None,
+                            })
+                            .min(),
+                    });
+                }
+            }
+        }
+    }
+
+    inconsistencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ixbrl_parser::parse_inline_xbrl;
+    use crate::models::linkbase::Relationship;
+
+    const ROLE: &str = "http://disclosure.edinet-fsa.go.jp/role/jppfs/rol_ConsolidatedBalanceSheet";
+
+    /// Current assets reported as 300 from items of 100 and 201, all at
+    /// `decimals`.
+    fn check(decimals: &str) -> Vec<CalculationInconsistency> {
+        let fact = |concept: &str, value: &str| format!(
+            r#"<ix:nonFraction name="jppfs_cor:{}" contextRef="CurrentYearInstant" unitRef="JPY" decimals="{}">{}</ix:nonFraction>"#,
+            concept, decimals, value,
+        );
+        let document = format!(
+            concat!(
+                r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL" xmlns:xbrli="http://www.xbrl.org/2003/instance"><body>"#,
+                r#"<ix:header><ix:resources>"#,
+                r#"<xbrli:context id="CurrentYearInstant"><xbrli:entity><xbrli:identifier scheme="http://disclosure.edinet-fsa.go.jp">E00000-000</xbrli:identifier></xbrli:entity>"#,
+                r#"<xbrli:period><xbrli:instant>2024-03-31</xbrli:instant></xbrli:period></xbrli:context>"#,
+                r#"<xbrli:unit id="JPY"><xbrli:measure>iso4217:JPY</xbrli:measure></xbrli:unit>"#,
+                r#"</ix:resources></ix:header>{}{}{}</body></html>"#,
+            ),
+            fact("CurrentAssets", "300"),
+            fact("CashAndDeposits", "100"),
+            fact("NotesAndAccountsReceivableTrade", "201"),
+        );
+        let xbrl = parse_inline_xbrl(&[document]).unwrap();
+
+        let arc = |to: &str, order: f64| Relationship {
+            arcrole: SUMMATION_ITEM_ARCROLE.to_string(),
+            from: "jppfs_cor:CurrentAssets".to_string(),
+            to: format!("jppfs_cor:{}", to),
+            order,
+            weight: Some(1.0),
+            preferred_label: None,
+        };
+        let network = Network {
+            role: ROLE.to_string(),
+            relationships: vec![arc("CashAndDeposits", 1.0), arc("NotesAndAccountsReceivableTrade", 2.0)],
+        };
+
+        check_calculations(&xbrl, &[network])
+    }
+
+    #[test]
+    fn tolerates_rounding_of_every_reported_value() {
+        // 300 ± 0.5 can meet 301 ± 1.0.
+        assert!(check("0").is_empty());
+    }
+
+    #[test]
+    fn flags_exact_values_that_do_not_add_up() {
+        let inconsistencies = check("INF");
+        assert_eq!(inconsistencies.len(), 1);
+        assert_eq!(inconsistencies[0].concept, "jppfs_cor:CurrentAssets");
+        assert_eq!(inconsistencies[0].reported, 300.0);
+        assert_eq!(inconsistencies[0].computed, 301.0);
+        assert_eq!(inconsistencies[0].decimals, None);
+    }
+
+    #[test]
+    fn rounding_error_is_half_the_last_digit() {
+        let value = |decimals| ReportedValue { unit_ref: "JPY", value: 0.0, decimals };
+        assert_eq!(value(Some(Decimals::Finite(-6))).rounding_error(), 500_000.0);
+        assert_eq!(value(Some(Decimals::Finite(2))).rounding_error(), 0.005);
+        assert_eq!(value(Some(Decimals::Infinite)).rounding_error(), 0.0);
+        assert_eq!(value(None).rounding_error(), 0.0);
+    }
+}
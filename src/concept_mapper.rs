@@ -0,0 +1,164 @@
+// concept_mapper.rs
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use chrono::Duration;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::models::concept_mapping::{ConceptMappings, FieldPeriod, StatementMapping};
+use crate::models::financial_statements::{
+    AccountingStandard, BalanceSheet, CashFlowStatement, IncomeStatement, BankIncomeStatement, BankBalanceSheet,
+    InsuranceIncomeStatement, InsuranceBalanceSheet, SecuritiesIncomeStatement, SecuritiesBalanceSheet,
+};
+use crate::models::xbrl::{DynamicXBRLContent, Period, UnitKind};
+
+/// The mappings shipped with the crate, used when no mapping file is found.
+const DEFAULT_MAPPINGS: &str = include_str!("../concept_mappings.json");
+
+/// Every leaf field of a serialized value as a dotted path
+/// (`assets.total_assets`) with its value.
+pub fn field_values<T: Serialize>(value: &T) -> Vec<(String, Value)> {
+    fn collect(value: Value, path: &str, out: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(fields) => {
+                for (name, field) in fields {
+                    let path = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+                    collect(field, &path, out);
+                }
+            }
+            leaf => out.push((path.to_string(), leaf)),
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(serde_json::to_value(value).unwrap_or_default(), "", &mut out);
+    out
+}
+
+/// Checks that every field a mapping section names exists on `T`.
+fn validate<T: Serialize + Default>(section: &str, mapping: &StatementMapping) -> Result<(), Box<dyn Error>> {
+    let fields = field_values(&T::default());
+    for field in mapping.keys() {
+        if !fields.iter().any(|(path, _)| path == field) {
+            return Err(format!("Unknown field {} in the {} concept mapping", field, section).into());
+        }
+    }
+    Ok(())
+}
+
+/// Loads the concept mappings from `path`, falling back to the built-in
+/// mappings when there is no file there.
+pub fn load_concept_mappings(path: &Path) -> Result<ConceptMappings, Box<dyn Error>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => DEFAULT_MAPPINGS.to_string(),
+        Err(e) => return Err(e.into()),
+    };
+    let mappings: ConceptMappings = serde_json::from_str(&content)?;
+
+    validate::<IncomeStatement>("income_statement", &mappings.income_statement)?;
+    validate::<BalanceSheet>("balance_sheet", &mappings.balance_sheet)?;
+    validate::<CashFlowStatement>("cash_flow_statement", &mappings.cash_flow_statement)?;
+    validate::<BankIncomeStatement>("bank_income_statement", &mappings.bank_income_statement)?;
+    validate::<BankBalanceSheet>("bank_balance_sheet", &mappings.bank_balance_sheet)?;
+    validate::<InsuranceIncomeStatement>("insurance_income_statement", &mappings.insurance_income_statement)?;
+    validate::<InsuranceBalanceSheet>("insurance_balance_sheet", &mappings.insurance_balance_sheet)?;
+    validate::<SecuritiesIncomeStatement>("securities_income_statement", &mappings.securities_income_statement)?;
+    validate::<SecuritiesBalanceSheet>("securities_balance_sheet", &mappings.securities_balance_sheet)?;
+
+    Ok(mappings)
+}
+
+/// Fills a normalized statement from the facts of the consolidated or
+/// non-consolidated statements for `period`. Each field takes the first
+/// concept of its mapping that is reported, in the filing's accounting
+/// standard and in a currency. Fails when the mapped values do not
+/// deserialize into `T`, e.g. a field mapped onto a non-numeric member.
+pub fn extract_mapped<T: DeserializeOwned>(
+    xbrl: &DynamicXBRLContent,
+    mapping: &StatementMapping,
+    standard: AccountingStandard,
+    consolidated: bool,
+    period: &Period,
+) -> Result<T, Box<dyn Error>> {
+    let field_period = |field: FieldPeriod| match (field, *period) {
+        (FieldPeriod::Statement, period) => Some(period),
+        (FieldPeriod::Opening, Period::Duration { start, .. }) => Some(Period::Instant(start - Duration::days(1))),
+        (FieldPeriod::Closing, Period::Duration { end, .. }) => Some(Period::Instant(end)),
+        _ => None,
+    };
+
+    let mut values: HashMap<(&str, Period), f64> = HashMap::new();
+    for fact in xbrl.facts() {
+        if !fact.name.starts_with(standard.statement_prefix()) || fact.unit_kind() != Some(UnitKind::Monetary) {
+            continue;
+        }
+        let Some(context) = fact.context_ref.as_ref().and_then(|c| xbrl.contexts.get(c)) else {
+            continue;
+        };
+        if let (true, Some(value)) = (context.is_statement_context(consolidated), fact.numeric_value().and_then(|f| f.value)) {
+            values.insert((fact.name.as_str(), context.period), value);
+        }
+    }
+
+    let mut root = Map::new();
+    for (field, field_mapping) in mapping {
+        let Some(period) = field_period(field_mapping.period()) else {
+            continue;
+        };
+        let Some(value) = field_mapping.concepts().iter().find_map(|c| values.get(&(c.as_str(), period))) else {
+            continue;
+        };
+
+        let mut path: Vec<&str> = field.split('.').collect();
+        let leaf = path.pop().unwrap_or_default();
+        let mut object = &mut root;
+        for part in path {
+            object = object.entry(part)
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .expect("validated field paths only nest into sections");
+        }
+        object.insert(leaf.to_string(), Value::from(*value));
+    }
+
+    serde_json::from_value(Value::Object(root))
+        .map_err(|e| format!("Mapped values do not fit the statement: {}", e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use crate::ixbrl_parser::parse_inline_xbrl;
+
+    #[derive(Deserialize, Debug)]
+    struct Mislabelled {
+        #[allow(dead_code)]
+        net_sales: Option<String>,
+    }
+
+    #[test]
+    fn reports_values_that_do_not_fit_the_statement() {
+        let document = concat!(
+            r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL" xmlns:xbrli="http://www.xbrl.org/2003/instance"><body>"#,
+            r#"<ix:header><ix:resources>"#,
+            r#"<xbrli:context id="CurrentYearDuration"><xbrli:entity><xbrli:identifier scheme="http://disclosure.edinet-fsa.go.jp">E00000-000</xbrli:identifier></xbrli:entity>"#,
+            r#"<xbrli:period><xbrli:startDate>2023-04-01</xbrli:startDate><xbrli:endDate>2024-03-31</xbrli:endDate></xbrli:period></xbrli:context>"#,
+            r#"<xbrli:unit id="JPY"><xbrli:measure>iso4217:JPY</xbrli:measure></xbrli:unit>"#,
+            r#"</ix:resources></ix:header>"#,
+            r#"<ix:nonFraction name="jppfs_cor:NetSales" contextRef="CurrentYearDuration" unitRef="JPY" decimals="0">100</ix:nonFraction>"#,
+            r#"</body></html>"#,
+        );
+        let xbrl = parse_inline_xbrl(&[document.to_string()]).unwrap();
+        let mapping: StatementMapping = serde_json::from_str(r#"{"net_sales": ["jppfs_cor:NetSales"]}"#).unwrap();
+        let period = xbrl.current_duration().unwrap();
+
+        let result = extract_mapped::<Mislabelled>(&xbrl, &mapping, AccountingStandard::JapanGaap, true, &period);
+        assert!(result.is_err());
+    }
+}
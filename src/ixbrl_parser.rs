@@ -0,0 +1,308 @@
+// ixbrl_parser.rs
+use std::collections::HashMap;
+use std::error::Error;
+use quick_xml::Reader;
+use quick_xml::events::Event;
+
+use crate::models::xbrl::{XBRLElement, DynamicXBRLContent};
+use crate::xbrl_parser::{start_element, local_name, parse_context, parse_unit, resolve_units};
+
+/// A fact or continuation whose content is still being read, both as
+/// markup (tags and entities as filed) and as unescaped text. Which one is
+/// the value depends on the `escape` flag of the fact, which continuations
+/// do not carry themselves.
+struct OpenFragment {
+    element: XBRLElement,
+    markup: String,
+    text: String,
+}
+
+/// Parses the pages of an inline XBRL document set (the `*_ixbrl.htm` files
+/// of one filing) into the same model as `parse_dynamic_xbrl`. Contexts and
+/// units come from `ix:resources`, facts from `ix:nonFraction` and
+/// `ix:nonNumeric` anywhere in the pages, including `ix:hidden`. Facts split
+/// across `ix:continuation` elements are joined back together.
+pub fn parse_inline_xbrl(documents: &[String]) -> Result<DynamicXBRLContent, Box<dyn Error>> {
+    let mut namespaces = HashMap::new();
+    let mut contexts = HashMap::new();
+    let mut units = HashMap::new();
+    let mut facts = Vec::new();
+    let mut continuations = HashMap::new();
+
+    for document in documents {
+        let mut reader = Reader::from_str(document.trim_start_matches('\u{feff}'));
+        reader.trim_text(false);
+
+        let mut resources_stack: Vec<XBRLElement> = Vec::new();
+        let mut in_resources = false;
+        let mut exclude_depth = 0usize;
+        let mut open: Vec<OpenFragment> = Vec::new();
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => {
+                    let element = start_element(&e, &mut namespaces)?;
+
+                    match element.name.as_str() {
+                        "ix:resources" => in_resources = true,
+                        "ix:exclude" => exclude_depth += 1,
+                        "ix:nonFraction" | "ix:nonNumeric" | "ix:continuation" => {
+                            open.push(open_fragment(element));
+                        }
+                        _ if in_resources => resources_stack.push(element),
+                        name if !name.starts_with("ix:") && exclude_depth == 0 => {
+                            let tag = format!("<{}>", String::from_utf8_lossy(&e));
+                            for fragment in open.iter_mut() {
+                                fragment.markup.push_str(&tag);
+                            }
+                        }
+                        _ => {}
+                    }
+                },
+                Ok(Event::Empty(e)) => {
+                    let element = start_element(&e, &mut namespaces)?;
+
+                    match element.name.as_str() {
+                        "ix:nonFraction" | "ix:nonNumeric" => facts.push(open_fragment(element).element),
+                        _ if in_resources => {
+                            if let Some(parent) = resources_stack.last_mut() {
+                                parent.children.push(element);
+                            }
+                        }
+                        name if !name.starts_with("ix:") && exclude_depth == 0 => {
+                            let tag = format!("<{}/>", String::from_utf8_lossy(&e));
+                            for fragment in open.iter_mut() {
+                                fragment.markup.push_str(&tag);
+                            }
+                        }
+                        _ => {}
+                    }
+                },
+                Ok(Event::Text(e)) => {
+                    if exclude_depth > 0 {
+                        continue;
+                    }
+
+                    if in_resources {
+                        if let Some(element) = resources_stack.last_mut() {
+                            let text = String::from_utf8(e.to_vec())?;
+                            if !text.trim().is_empty() {
+                                element.value = Some(text.trim().to_string());
+                            }
+                        }
+                        continue;
+                    }
+
+                    let raw = String::from_utf8(e.to_vec())?;
+                    let unescaped = e.unescape().map(|t| t.into_owned()).unwrap_or_else(|_| raw.clone());
+                    for fragment in open.iter_mut() {
+                        fragment.markup.push_str(&raw);
+                        fragment.text.push_str(&unescaped);
+                    }
+                },
+                Ok(Event::End(e)) => {
+                    let name = String::from_utf8(e.name().as_ref().to_vec())?;
+
+                    match name.as_str() {
+                        "ix:resources" => in_resources = false,
+                        "ix:exclude" => exclude_depth = exclude_depth.saturating_sub(1),
+                        "ix:nonFraction" | "ix:nonNumeric" | "ix:continuation" => {
+                            if let Some(mut fragment) = open.pop() {
+                                if name == "ix:continuation" {
+                                    if let Some(id) = fragment.element.attributes.get("id").cloned() {
+                                        continuations.insert(id, fragment);
+                                    }
+                                } else {
+                                    fragment.element.value = Some(if is_escaped(&fragment.element) {
+                                        fragment.markup
+                                    } else if fragment.element.attributes.contains_key("continuedAt") {
+                                        // The end is trimmed once the continuations are joined.
+                                        fragment.text.trim_start().to_string()
+                                    } else {
+                                        let text = fragment.text.trim();
+                                        fragment.element.attributes.get("format")
+                                            .and_then(|f| transform_date(text, f))
+                                            .unwrap_or_else(|| text.to_string())
+                                    });
+                                    facts.push(fragment.element);
+                                }
+                            }
+                        }
+                        _ if in_resources => {
+                            if let Some(element) = resources_stack.pop() {
+                                match local_name(&element.name) {
+                                    "context" => {
+                                        if let Some(context) = parse_context(&element) {
+                                            contexts.insert(context.id.clone(), context);
+                                        }
+                                    }
+                                    "unit" => {
+                                        if let Some(unit) = parse_unit(&element) {
+                                            units.insert(unit.id.clone(), unit);
+                                        }
+                                    }
+                                    _ => {}
+                                }
+
+                                if let Some(parent) = resources_stack.last_mut() {
+                                    parent.children.push(element);
+                                }
+                            }
+                        }
+                        name if !name.starts_with("ix:") && exclude_depth == 0 => {
+                            let tag = format!("</{}>", name);
+                            for fragment in open.iter_mut() {
+                                fragment.markup.push_str(&tag);
+                            }
+                        }
+                        _ => {}
+                    }
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(format!("Error parsing inline XBRL: {:?}", e).into()),
+                _ => (),
+            }
+        }
+    }
+
+    for fact in &mut facts {
+        join_continuations(fact, &continuations);
+    }
+
+    resolve_units(&mut facts, &units);
+
+    Ok(DynamicXBRLContent {
+        namespaces,
+        contexts,
+        units,
+        elements: facts,
+    })
+}
+
+/// Turns an `ix:` fact element into a fact named after its concept, keeping
+/// the remaining attributes (`decimals`, `scale`, `format`, ...) as-is.
+fn open_fragment(mut element: XBRLElement) -> OpenFragment {
+    if let Some(concept) = element.attributes.remove("name") {
+        element.name = concept;
+    }
+
+    OpenFragment {
+        element,
+        markup: String::new(),
+        text: String::new(),
+    }
+}
+
+/// Whether an `ix:nonNumeric` fact's content is markup to be kept as-is.
+fn is_escaped(element: &XBRLElement) -> bool {
+    element.attributes.get("escape").is_some_and(|v| v == "true" || v == "1")
+}
+
+/// Appends the `ix:continuation` chain of a fact to its value, as markup for
+/// escaped facts and as text otherwise.
+fn join_continuations(fact: &mut XBRLElement, continuations: &HashMap<String, OpenFragment>) {
+    let escape = is_escaped(fact);
+    let mut next = fact.attributes.get("continuedAt").cloned();
+    // A chain visits each continuation at most once, even if it loops.
+    for _ in 0..continuations.len() {
+        let Some(continuation) = next.and_then(|id| continuations.get(&id)) else {
+            break;
+        };
+        let value = fact.value.get_or_insert_with(String::new);
+        value.push_str(if escape { &continuation.markup } else { &continuation.text });
+        next = continuation.element.attributes.get("continuedAt").cloned();
+    }
+
+    if !escape {
+        if let Some(value) = &mut fact.value {
+            value.truncate(value.trim_end().len());
+        }
+    }
+}
+
+/// Applies the Japanese date transformations used on EDINET cover pages
+/// (`ixt:dateerayearmonthdayjp`, `ixt:dateyearmonthdaycjk`), returning an
+/// ISO date like the one in the generated instance.
+fn transform_date(text: &str, format: &str) -> Option<String> {
+    let normalized: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            '０'..='９' => char::from_u32(c as u32 - '０' as u32 + '0' as u32).unwrap_or(c),
+            _ => c,
+        })
+        .collect();
+
+    let (base_year, rest) = match format.rsplit(':').next()? {
+        "dateerayearmonthdayjp" => {
+            const ERAS: [(&str, i32); 5] = [("令和", 2018), ("平成", 1988), ("昭和", 1925), ("大正", 1911), ("明治", 1867)];
+            let (era, offset) = ERAS.iter().find(|(era, _)| normalized.starts_with(era))?;
+            (*offset, normalized[era.len()..].replacen("元", "1", 1))
+        }
+        "dateyearmonthdaycjk" => (0, normalized),
+        _ => return None,
+    };
+
+    let mut parts = rest.split(['年', '月', '日']).filter(|p| !p.is_empty());
+    let year = parts.next()?.parse::<i32>().ok()? + base_year;
+    let month = parts.next()?.parse::<u32>().ok()?;
+    let day = parts.next()?.parse::<u32>().ok()?;
+
+    chrono::NaiveDate::from_ymd_opt(year, month, day).map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &str) -> DynamicXBRLContent {
+        let document = format!(
+            r#"<html xmlns="http://www.w3.org/1999/xhtml" xmlns:ix="http://www.xbrl.org/2013/inlineXBRL"><body>{}</body></html>"#,
+            body,
+        );
+        parse_inline_xbrl(&[document]).unwrap()
+    }
+
+    fn value<'a>(xbrl: &'a DynamicXBRLContent, concept: &str) -> Option<&'a str> {
+        xbrl.elements.iter().find(|e| e.name == concept)?.value.as_deref()
+    }
+
+    #[test]
+    fn converts_japanese_era_dates() {
+        assert_eq!(transform_date("令和元年5月1日", "ixt:dateerayearmonthdayjp").as_deref(), Some("2019-05-01"));
+        assert_eq!(transform_date("平成３１年 ４月３０日", "ixt:dateerayearmonthdayjp").as_deref(), Some("2019-04-30"));
+        assert_eq!(transform_date("2024年6月27日", "ixt:dateyearmonthdaycjk").as_deref(), Some("2024-06-27"));
+        assert_eq!(transform_date("令和2年2月30日", "ixt:dateerayearmonthdayjp"), None);
+        assert_eq!(transform_date("2024-06-27", "ixt:numdotdecimal"), None);
+    }
+
+    #[test]
+    fn transforms_date_facts() {
+        let xbrl = parse(r#"<ix:nonNumeric name="jpdei_cor:CurrentFiscalYearEndDateDEI" contextRef="FilingDateInstant" format="ixt:dateerayearmonthdayjp">令和元年 12月31日</ix:nonNumeric>"#);
+        assert_eq!(value(&xbrl, "jpdei_cor:CurrentFiscalYearEndDateDEI"), Some("2019-12-31"));
+    }
+
+    #[test]
+    fn joins_continuations_of_escaped_facts_as_markup() {
+        let xbrl = parse(concat!(
+            r#"<ix:nonNumeric name="jpcrp_cor:BusinessRisksTextBlock" contextRef="FilingDateInstant" escape="true" continuedAt="c1">"#,
+            r#"<p>first &amp; part</p></ix:nonNumeric>"#,
+            r#"<div><ix:continuation id="c1" continuedAt="c2">second <b>bold</b></ix:continuation></div>"#,
+            r#"<ix:continuation id="c2">&lt;end&gt;</ix:continuation>"#,
+        ));
+        assert_eq!(
+            value(&xbrl, "jpcrp_cor:BusinessRisksTextBlock"),
+            Some("<p>first &amp; part</p>second <b>bold</b>&lt;end&gt;"),
+        );
+    }
+
+    #[test]
+    fn joins_continuations_of_plain_facts_as_text() {
+        let xbrl = parse(concat!(
+            r#"<ix:nonNumeric name="jpcrp_cor:NameOfRepresentative" contextRef="FilingDateInstant" continuedAt="c1">"#,
+            r#"<span>代表取締役 &amp; </span></ix:nonNumeric>"#,
+            r#"<ix:continuation id="c1"><b>山田 太郎</b> </ix:continuation>"#,
+        ));
+        assert_eq!(value(&xbrl, "jpcrp_cor:NameOfRepresentative"), Some("代表取締役 & 山田 太郎"));
+    }
+}
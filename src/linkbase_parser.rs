@@ -0,0 +1,192 @@
+// linkbase_parser.rs
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::models::xbrl::XBRLElement;
+use crate::models::linkbase::{Label, LabelLinkbase, Network, Relationship};
+use crate::models::taxonomy::Taxonomy;
+use crate::xbrl_archive::{XBRLArchive, DocumentKind, EntryKind};
+use crate::xbrl_parser::{parse_dynamic_xbrl, local_name};
+
+/// Turns a locator href such as `jppfs_cor_2013-08-31.xsd#jppfs_cor_NetSales`
+/// into a concept QName (`jppfs_cor:NetSales`). EDINET element ids are the
+/// namespace prefix and the element name joined by the last underscore.
+pub fn concept_from_href(href: &str) -> Option<String> {
+    let (_, id) = href.split_once('#')?;
+    let (prefix, name) = id.rsplit_once('_')?;
+    Some(format!("{}:{}", prefix, name))
+}
+
+/// Collects every extended link element (`link:labelLink`,
+/// `link:presentationLink`, ...) with the given local name.
+pub fn extended_links<'a>(elements: &'a [XBRLElement], link_name: &str, out: &mut Vec<&'a XBRLElement>) {
+    for element in elements {
+        if local_name(&element.name) == link_name {
+            out.push(element);
+        } else {
+            extended_links(&element.children, link_name, out);
+        }
+    }
+}
+
+/// Maps the `xlink:label` of each locator in an extended link to its concept.
+pub fn locators(link: &XBRLElement) -> HashMap<&str, String> {
+    link.children.iter()
+        .filter(|c| local_name(&c.name) == "loc")
+        .filter_map(|loc| {
+            let label = loc.attributes.get("xlink:label")?;
+            let concept = concept_from_href(loc.attributes.get("xlink:href")?)?;
+            Some((label.as_str(), concept))
+        })
+        .collect()
+}
+
+pub fn parse_label_linkbase(content: &str) -> Result<LabelLinkbase, Box<dyn Error>> {
+    let document = parse_dynamic_xbrl(content)?;
+    let mut linkbase = LabelLinkbase::default();
+
+    let mut links = Vec::new();
+    extended_links(&document.elements, "labelLink", &mut links);
+
+    for link in links {
+        let locs = locators(link);
+
+        let mut resources: HashMap<&str, Vec<&XBRLElement>> = HashMap::new();
+        for resource in link.children.iter().filter(|c| local_name(&c.name) == "label") {
+            if let Some(label) = resource.attributes.get("xlink:label") {
+                resources.entry(label.as_str()).or_default().push(resource);
+            }
+        }
+
+        for arc in link.children.iter().filter(|c| local_name(&c.name) == "labelArc") {
+            let (Some(from), Some(to)) = (arc.attributes.get("xlink:from"), arc.attributes.get("xlink:to")) else {
+                continue;
+            };
+            let (Some(concept), Some(targets)) = (locs.get(from.as_str()), resources.get(to.as_str())) else {
+                continue;
+            };
+
+            for resource in targets {
+                linkbase.insert(Label {
+                    concept: concept.clone(),
+                    lang: resource.attributes.get("xml:lang").cloned().unwrap_or_default(),
+                    role: resource.attributes.get("xlink:role").cloned().unwrap_or_default(),
+                    text: resource.value.clone().unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    Ok(linkbase)
+}
+
+/// Parses the arcs of every `link_name` extended link (e.g. `presentationLink`
+/// with `presentationArc`) into one network per link role.
+pub fn parse_relationships(content: &str, link_name: &str, arc_name: &str) -> Result<Vec<Network>, Box<dyn Error>> {
+    let document = parse_dynamic_xbrl(content)?;
+    let mut networks: Vec<Network> = Vec::new();
+
+    let mut links = Vec::new();
+    extended_links(&document.elements, link_name, &mut links);
+
+    for link in links {
+        let role = link.attributes.get("xlink:role").cloned().unwrap_or_default();
+        let locs = locators(link);
+
+        let relationships = link.children.iter()
+            .filter(|c| local_name(&c.name) == arc_name)
+            .filter_map(|arc| {
+                let from = locs.get(arc.attributes.get("xlink:from")?.as_str())?;
+                let to = locs.get(arc.attributes.get("xlink:to")?.as_str())?;
+                Some(Relationship {
+                    arcrole: arc.attributes.get("xlink:arcrole").cloned().unwrap_or_default(),
+                    from: from.clone(),
+                    to: to.clone(),
+                    order: arc.attributes.get("order").and_then(|o| o.parse().ok()).unwrap_or(1.0),
+                    weight: arc.attributes.get("weight").and_then(|w| w.parse().ok()),
+                    preferred_label: arc.attributes.get("preferredLabel").cloned(),
+                })
+            });
+
+        match networks.iter_mut().find(|n| n.role == role) {
+            Some(network) => network.relationships.extend(relationships),
+            None => networks.push(Network {
+                role,
+                relationships: relationships.collect(),
+            }),
+        }
+    }
+
+    Ok(networks)
+}
+
+/// Loads the labels of `concepts` from the standard taxonomy's label
+/// linkbases, then the Japanese and English label linkbases shipped in the
+/// archive, whose labels take precedence. Standard linkbases are parsed once
+/// and kept in `cache` across filings; unreadable ones are skipped.
+pub fn load_labels(
+    archive: &mut XBRLArchive,
+    kind: DocumentKind,
+    taxonomy: &Taxonomy,
+    concepts: &HashSet<&str>,
+    cache: &mut HashMap<PathBuf, LabelLinkbase>,
+) -> Result<LabelLinkbase, Box<dyn Error>> {
+    let mut labels = LabelLinkbase::default();
+
+    for path in &taxonomy.label_linkbases {
+        if !cache.contains_key(path) {
+            match fs::read_to_string(path).map_err(Into::into).and_then(|c| parse_label_linkbase(&c)) {
+                Ok(linkbase) => {
+                    cache.insert(path.clone(), linkbase);
+                }
+                Err(e) => {
+                    eprintln!("Skipping unreadable label linkbase {}: {}", path.display(), e);
+                    continue;
+                }
+            }
+        }
+        let standard = &cache[path];
+        for concept in concepts {
+            for label in standard.labels.get(*concept).into_iter().flatten() {
+                labels.insert(label.clone());
+            }
+        }
+    }
+
+    for content in archive.read_entries(kind, &[EntryKind::LabelLinkbase, EntryKind::EnglishLabelLinkbase])? {
+        for label in parse_label_linkbase(&content)?.labels.into_values().flatten() {
+            labels.replace(label);
+        }
+    }
+
+    Ok(labels)
+}
+
+pub fn load_presentation(archive: &mut XBRLArchive, kind: DocumentKind) -> Result<Vec<Network>, Box<dyn Error>> {
+    let mut networks = Vec::new();
+    for content in archive.read_entries(kind, &[EntryKind::PresentationLinkbase])? {
+        networks.extend(parse_relationships(&content, "presentationLink", "presentationArc")?);
+    }
+
+    Ok(networks)
+}
+
+pub fn load_calculation(archive: &mut XBRLArchive, kind: DocumentKind) -> Result<Vec<Network>, Box<dyn Error>> {
+    let mut networks = Vec::new();
+    for content in archive.read_entries(kind, &[EntryKind::CalculationLinkbase])? {
+        networks.extend(parse_relationships(&content, "calculationLink", "calculationArc")?);
+    }
+
+    Ok(networks)
+}
+
+pub fn load_definition(archive: &mut XBRLArchive, kind: DocumentKind) -> Result<Vec<Network>, Box<dyn Error>> {
+    let mut networks = Vec::new();
+    for content in archive.read_entries(kind, &[EntryKind::DefinitionLinkbase])? {
+        networks.extend(parse_relationships(&content, "definitionLink", "definitionArc")?);
+    }
+
+    Ok(networks)
+}
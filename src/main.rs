@@ -1,131 +1,403 @@
 use std::env;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::path::PathBuf;
 use tokio::time::{sleep, Duration};
 use chrono::{NaiveDate, Duration as ChronoDuration};
 use dotenv::dotenv;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::collections::HashMap;
-use serde_json::Value as JsonValue;
-use std::io::{Read, Write};
-use zip::ZipArchive;
-use quick_xml::Reader;
-use quick_xml::events::Event;
-use rusqlite::{params, Connection};
+use std::fs;
+use rusqlite::{params, params_from_iter, Connection};
+use rusqlite::types::Value as SqlValue;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 mod models;
 mod edinet_api_client;
+mod xbrl_parser;
+mod ixbrl_parser;
+mod xbrl_archive;
+mod linkbase_parser;
+mod statement_builder;
+mod calculation_checker;
+mod schema_loader;
+mod text_blocks;
+mod concept_mapper;
 
-use models::xbrl::{XBRLElement, DynamicXBRLContent};
-use models::financial_statements::{IncomeStatement, BalanceSheet};
+use models::xbrl::{
+    XBRLElement, DimensionMember, DynamicXBRLContent, Period, ReportedFact, UnitKind,
+    CONSOLIDATION_AXIS, NON_CONSOLIDATED_MEMBER, EQUITY_COMPONENT_AXES, OPERATING_SEGMENTS_AXIS,
+};
+use models::financial_statements::{
+    IncomeStatement, BalanceSheet, CashFlowStatement, ChangesInEquityStatement, EquityChange, EquityLineKind,
+    AccountingStandard, PerShareData, SegmentValue, Industry, BankIncomeStatement, BankBalanceSheet,
+    InsuranceIncomeStatement, InsuranceBalanceSheet, SecuritiesIncomeStatement, SecuritiesBalanceSheet,
+};
+use models::concept_mapping::StatementMapping;
+use models::dei::DocumentEntityInfo;
+use models::document_type::{is_supported_doc_type, form_of_file};
+use models::linkbase::{Hypercube, Network, STANDARD_LABEL_ROLE, TERSE_LABEL_ROLE, VERBOSE_LABEL_ROLE};
 use edinet_api_client::EdinetApiClient;
+use xbrl_parser::parse_dynamic_xbrl;
+use ixbrl_parser::parse_inline_xbrl;
+use xbrl_archive::{XBRLArchive, DocumentKind};
+use linkbase_parser::{load_labels, load_presentation, load_calculation, load_definition};
+use statement_builder::build_presented_statements;
+use calculation_checker::check_calculations;
+use schema_loader::load_taxonomy;
+use text_blocks::extract_text_blocks;
+use concept_mapper::{load_concept_mappings, extract_mapped, field_values};
 
-fn extract_income_statement(xbrl: &DynamicXBRLContent) -> IncomeStatement {
-    let mut stmt = IncomeStatement::default();
-
-    fn set_value_if_match(stmt: &mut IncomeStatement, element_name: &str, val: f64) {
-        match element_name {
-            "jppfs_cor:NetSales" => stmt.net_sales = Some(val),
-            "jppfs_cor:CostOfSales" => stmt.cost_of_sales = Some(val),
-            "jppfs_cor:GrossProfit" => stmt.gross_profit = Some(val),
-            "jppfs_cor:SellingGeneralAndAdministrativeExpenses" => stmt.selling_general_admin = Some(val),
-            "jppfs_cor:OperatingIncome" => stmt.operating_income = Some(val),
-            "jppfs_cor:InterestIncomeNOI" => stmt.interest_income_noi = Some(val),
-            "jppfs_cor:DividendsIncomeNOI" => stmt.dividends_income_noi = Some(val),
-            "jppfs_cor:InterestAndDividendsIncomeNOI" => stmt.interest_and_dividends_income_noi = Some(val),
-            "jppfs_cor:PurchaseDiscountsNOI" => stmt.purchase_discounts_noi = Some(val),
-            "jppfs_cor:RentIncomeNOI" => stmt.rent_income_noi = Some(val),
-            "jppfs_cor:HouseRentIncomeNOI" => stmt.house_rent_income_noi = Some(val),
-            "jppfs_cor:OtherNOI" => stmt.other_noi = Some(val),
-            "jppfs_cor:NonOperatingIncome" => stmt.non_operating_income = Some(val),
-            "jppfs_cor:SalesDiscountsNOE" => stmt.sales_discounts_noe = Some(val),
-            "jppfs_cor:RentCostOfRealEstateNOE" => stmt.rent_cost_real_estate_noe = Some(val),
-            "jppfs_cor:OtherNOE" => stmt.other_noe = Some(val),
-            "jppfs_cor:NonOperatingExpenses" => stmt.non_operating_expenses = Some(val),
-            "jppfs_cor:OrdinaryIncome" => stmt.ordinary_income = Some(val),
-            "jppfs_cor:GainOnSalesOfNoncurrentAssetsEI" => stmt.gain_on_sales_of_noncurrent_assets_ei = Some(val),
-            "jppfs_cor:ExtraordinaryIncome" => stmt.extraordinary_income = Some(val),
-            "jppfs_cor:IncomeBeforeIncomeTaxes" => stmt.income_before_income_taxes = Some(val),
-            "jppfs_cor:IncomeTaxesCurrent" => stmt.income_taxes_current = Some(val),
-            "jppfs_cor:IncomeTaxesDeferred" => stmt.income_taxes_deferred = Some(val),
-            "jppfs_cor:IncomeTaxes" => stmt.income_taxes = Some(val),
-            "jppfs_cor:IncomeBeforeMinorityInterests" => stmt.income_before_minority_interests = Some(val),
-            "jppfs_cor:NetIncome" => stmt.net_income = Some(val),
-            _ => {}
-        }
-    }
+/// Tables of per-period figures that are rewritten with every filing and
+/// have an effective (latest amendment) view.
+const STATEMENT_TABLES: [&str; 10] = [
+    "income_statements", "balance_sheets", "cash_flow_statements", "per_share_data",
+    "bank_income_statements", "bank_balance_sheets", "insurance_income_statements",
+    "insurance_balance_sheets", "securities_income_statements", "securities_balance_sheets",
+];
 
-    fn visit_element(ele: &XBRLElement, stmt: &mut IncomeStatement) {
-        if let Some(ctx) = &ele.context_ref {
-            if ctx == "CurrentYTDDuration" {
-                if let Some(txt) = &ele.value {
-                    if let Ok(parsed_val) = txt.parse::<f64>() {
-                        set_value_if_match(stmt, &ele.name, parsed_val);
-                    }
+/// Reads per-share amounts for `period` and share counts at its end. Amounts
+/// are only taken in a currency per share and counts in shares. Share counts
+/// are the same for both scopes.
+fn extract_per_share_data(xbrl: &DynamicXBRLContent, consolidated: bool, period: &Period) -> PerShareData {
+    let mut data = PerShareData::default();
+    let Some(end) = period.end_date() else {
+        return data;
+    };
+    let instant = Period::Instant(end);
+
+    for fact in xbrl.facts() {
+        let (Some(ctx), Some(unit)) = (fact.context_ref.as_deref(), fact.unit.as_ref()) else {
+            continue;
+        };
+        let Some(val) = fact.numeric_value().and_then(|f| f.value) else {
+            continue;
+        };
+
+        match unit.kind() {
+            UnitKind::PerShare if xbrl.is_statement_context(ctx, period, consolidated) => match fact.name.as_str() {
+                "jpcrp_cor:BasicEarningsLossPerShareSummaryOfBusinessResults"
+                | "jpcrp_cor:BasicEarningsLossPerShareIFRSSummaryOfBusinessResults"
+                | "jpcrp_cor:BasicEarningsLossPerShareUSGAAPSummaryOfBusinessResults" => data.basic_eps = Some(val),
+                "jpcrp_cor:DilutedEarningsPerShareSummaryOfBusinessResults"
+                | "jpcrp_cor:DilutedEarningsLossPerShareIFRSSummaryOfBusinessResults"
+                | "jpcrp_cor:DilutedEarningsLossPerShareUSGAAPSummaryOfBusinessResults" => data.diluted_eps = Some(val),
+                "jpcrp_cor:DividendPaidPerShareSummaryOfBusinessResults" => data.dividends_per_share = Some(val),
+                _ => continue,
+            },
+            UnitKind::PerShare if xbrl.is_statement_context(ctx, &instant, consolidated) => match fact.name.as_str() {
+                "jpcrp_cor:NetAssetsPerShareSummaryOfBusinessResults"
+                | "jpcrp_cor:EquityAttributableToOwnersOfParentPerShareIFRSSummaryOfBusinessResults"
+                | "jpcrp_cor:EquityAttributableToOwnersOfParentPerShareUSGAAPSummaryOfBusinessResults" => data.book_value_per_share = Some(val),
+                _ => continue,
+            },
+            // Share counts describe the filer rather than a scope and are
+            // often only tagged as non-consolidated, which takes precedence.
+            UnitKind::Shares => {
+                let non_consolidated = xbrl.is_statement_context(ctx, &instant, false);
+                if !non_consolidated && !xbrl.is_statement_context(ctx, &instant, true) {
+                    continue;
+                }
+                let count = match fact.name.as_str() {
+                    "jpcrp_cor:TotalNumberOfIssuedSharesSummaryOfBusinessResults"
+                    | "jpcrp_cor:NumberOfIssuedSharesAsOfFiscalYearEndIssuedSharesTotalNumberOfSharesEtc" => &mut data.issued_shares,
+                    "jpcrp_cor:TotalNumberOfSharesHeldTreasurySharesEtc" => &mut data.treasury_shares,
+                    _ => continue,
+                };
+                if non_consolidated || count.is_none() {
+                    *count = Some(val);
                 }
             }
+            _ => continue,
         }
 
-        for child in &ele.children {
-            visit_element(child, stmt);
+        if unit.kind() == UnitKind::PerShare {
+            data.currency = unit.numerator.first().map(|m| m.trim_start_matches("iso4217:").to_string());
         }
     }
 
-    for e in &xbrl.elements {
-        visit_element(e, &mut stmt);
+    data
+}
+
+/// Reads the segment information: every numeric fact whose only dimension is
+/// a member of the operating segments axis, for any period it is reported
+/// for (current and comparative). Facts shown more than once are kept once.
+fn extract_segment_values(xbrl: &DynamicXBRLContent) -> Vec<SegmentValue> {
+    let mut seen = HashSet::new();
+    let mut values: Vec<SegmentValue> = Vec::new();
+
+    for fact in xbrl.facts() {
+        let Some(context) = fact.context_ref.as_ref().and_then(|c| xbrl.contexts.get(c)) else {
+            continue;
+        };
+        let mut dimensions = context.dimensions();
+        let segment = match (dimensions.next(), dimensions.next()) {
+            (Some(d), None) if d.dimension == OPERATING_SEGMENTS_AXIS => &d.member,
+            _ => continue,
+        };
+        let (Some(value), Some(_)) = (fact.numeric_value().and_then(|f| f.value), context.period.end_date()) else {
+            continue;
+        };
+        if !seen.insert((segment.as_str(), fact.name.as_str(), context.period)) {
+            continue;
+        }
+
+        values.push(SegmentValue {
+            segment: segment.clone(),
+            concept: fact.name.clone(),
+            period: context.period,
+            unit_ref: fact.unit_ref.clone(),
+            value,
+        });
     }
 
-    stmt
+    values
 }
 
-fn extract_balance_sheet(xbrl: &DynamicXBRLContent) -> BalanceSheet {
-    let mut sheet = BalanceSheet::default();
-
-    fn set_value_if_match(sheet: &mut BalanceSheet, element_name: &str, val: f64) {
-        match element_name {
-            "jppfs_cor:CashAndDeposits" => sheet.assets.cash_and_deposits = Some(val),
-            "jppfs_cor:NotesAndAccountsReceivableTrade" => sheet.assets.notes_and_accounts_receivable_trade = Some(val),
-            "jppfs_cor:ShortTermInvestmentSecurities" => sheet.assets.short_term_investment_securities = Some(val),
-            "jppfs_cor:Merchandise" => sheet.assets.merchandise = Some(val),
-            "jppfs_cor:PropertyPlantAndEquipment" => sheet.assets.property_plant_and_equipment = Some(val),
-            "jppfs_cor:IntangibleAssets" => sheet.assets.intangible_assets = Some(val),
-            "jppfs_cor:InvestmentsAndOtherAssets" => sheet.assets.investments_and_other_assets = Some(val),
-            "jppfs_cor:Assets" => sheet.assets.total_assets = Some(val),
-            
-            "jppfs_cor:CurrentLiabilities" => sheet.liabilities.current_liabilities = Some(val),
-            "jppfs_cor:NoncurrentLiabilities" => sheet.liabilities.noncurrent_liabilities = Some(val),
-            "jppfs_cor:Liabilities" => sheet.liabilities.total_liabilities = Some(val),
-            
-            "jppfs_cor:ShareholdersEquity" => sheet.equity.shareholders_equity = Some(val),
-            "jppfs_cor:ValuationAndTranslationAdjustments" => sheet.equity.valuation_and_translation_adjustments = Some(val),
-            "jppfs_cor:NetAssets" => sheet.equity.total_equity = Some(val),
-            _ => {}
-        }
+/// The consolidated or non-consolidated statements of a filing, with the
+/// accounting standard and industry layout they follow.
+type Scope<'a> = (&'a DynamicXBRLContent, AccountingStandard, Industry, bool);
+
+/// Statements of one kind, each with its scope (consolidated or not) and
+/// period.
+type ScopedStatements<T> = Vec<(bool, Period, T)>;
+
+/// The industry layout of the consolidated or non-consolidated statements.
+/// Filings without an industry code are recognized by the industry-specific
+/// concepts their statements are tagged with.
+fn statement_industry(xbrl: &DynamicXBRLContent, dei: &DocumentEntityInfo, consolidated: bool) -> Industry {
+    let code = if consolidated { &dei.consolidated_industry_code } else { &dei.industry_code };
+    if let Some(code) = code {
+        return Industry::parse(code);
     }
 
-    fn visit_element(ele: &XBRLElement, sheet: &mut BalanceSheet) {
-        if let Some(ctx) = &ele.context_ref {
-            if ctx == "CurrentQuarterInstant" {
-                if let Some(txt) = &ele.value {
-                    if let Ok(parsed_val) = txt.parse::<f64>() {
-                        set_value_if_match(sheet, &ele.name, parsed_val);
-                    }
-                }
+    let facts = xbrl.facts();
+    Industry::FINANCIAL
+        .into_iter()
+        .find(|industry| {
+            let suffix = industry.concept_suffix().unwrap_or_default();
+            facts.iter().any(|f| {
+                f.name.starts_with("jppfs_cor:")
+                    && f.name.ends_with(suffix)
+                    && f.context_ref.as_ref()
+                        .and_then(|c| xbrl.contexts.get(c))
+                        .is_some_and(|c| c.is_statement_context(consolidated))
+            })
+        })
+        .unwrap_or_default()
+}
+
+/// The per-share data of every scope for each period. Share counts are read
+/// from either scope, so a scope the filer prepares no statements for (no
+/// stored statements, or consolidated when the DEI says it does not
+/// consolidate) only gets rows with per-share amounts of its own.
+fn extract_per_share_rows(
+    scopes: &[Scope],
+    periods: &[Period],
+    stated_scopes: &HashSet<bool>,
+    dei: &DocumentEntityInfo,
+) -> Vec<(bool, Period, PerShareData)> {
+    scopes.iter()
+        .flat_map(|&(xbrl, _, _, c)| periods.iter().map(move |p| (c, *p, extract_per_share_data(xbrl, c, p))))
+        .filter(|(c, _, data)| {
+            let prepared = stated_scopes.contains(c) && !(*c && dei.consolidated == Some(false));
+            if prepared {
+                *data != PerShareData::default()
+            } else {
+                data.has_amounts()
             }
+        })
+        .collect()
+}
+
+/// One normalized statement per period for every scope that follows
+/// `industry` (every scope when `None`), leaving out periods with no figures.
+fn extract_statements<T: DeserializeOwned + Default + PartialEq>(
+    scopes: &[Scope],
+    industry: Option<Industry>,
+    periods: &[Period],
+    mapping: &StatementMapping,
+) -> Result<ScopedStatements<T>, Box<dyn Error>> {
+    let mut statements = Vec::new();
+    for &(xbrl, standard, _, c) in scopes.iter().filter(|&&(_, _, i, _)| industry.is_none_or(|industry| industry == i)) {
+        for period in periods {
+            let stmt: T = extract_mapped(xbrl, mapping, standard, c, period)?;
+            if stmt != T::default() {
+                statements.push((c, *period, stmt));
+            }
+        }
+    }
+    Ok(statements)
+}
+
+/// Concepts a filing reports or lays out: its facts and their dimension
+/// members, the concepts of its linkbase networks and its hypercubes.
+fn referenced_concepts<'a>(
+    xbrl: Option<&'a DynamicXBRLContent>,
+    networks: &[&'a [Network]],
+    hypercubes: &'a [Hypercube],
+) -> HashSet<&'a str> {
+    let mut concepts = HashSet::new();
+
+    if let Some(xbrl) = xbrl {
+        concepts.extend(xbrl.facts().into_iter().map(|f| f.name.as_str()));
+        for context in xbrl.contexts.values() {
+            concepts.extend(context.dimensions().filter(|d| !d.typed).flat_map(|d| [d.dimension.as_str(), d.member.as_str()]));
+        }
+    }
+    for relationship in networks.iter().flat_map(|n| n.iter()).flat_map(|n| &n.relationships) {
+        concepts.extend([relationship.from.as_str(), relationship.to.as_str()]);
+    }
+    for hypercube in hypercubes {
+        concepts.insert(hypercube.table.as_str());
+        concepts.extend(hypercube.primary_items.iter().map(String::as_str));
+        for dimension in &hypercube.dimensions {
+            concepts.insert(dimension.axis.as_str());
+            concepts.extend(dimension.members.iter().map(|m| m.member.as_str()));
+        }
+    }
+
+    concepts
+}
+
+/// Every numeric fact of the filing with its period, dimensions and unit
+/// resolved. A fact repeated in several inline XBRL documents is kept once.
+fn extract_numeric_facts(xbrl: &DynamicXBRLContent) -> Vec<ReportedFact> {
+    let mut seen = HashSet::new();
+    let mut facts = Vec::new();
+
+    for fact in xbrl.facts() {
+        let (Some(context_ref), Some(unit_ref)) = (fact.context_ref.as_deref(), fact.unit_ref.as_deref()) else {
+            continue;
+        };
+        let (Some(context), Some(numeric)) = (xbrl.contexts.get(context_ref), fact.numeric_value()) else {
+            continue;
+        };
+        if !seen.insert((fact.name.as_str(), context_ref)) {
+            continue;
         }
 
-        for child in &ele.children {
-            visit_element(child, sheet);
+        facts.push(ReportedFact {
+            concept: fact.name.clone(),
+            context_ref: context_ref.to_string(),
+            period: context.period,
+            dimensions: context.dimensions().cloned().collect(),
+            unit_ref: unit_ref.to_string(),
+            unit: fact.unit.as_ref().map(|u| u.measure()),
+            decimals: numeric.decimals,
+            value: numeric.value,
+        });
+    }
+
+    facts
+}
+
+/// Reads the filing's document and entity information. Nil and unparsable
+/// values are left out.
+fn extract_document_entity_info(xbrl: &DynamicXBRLContent) -> DocumentEntityInfo {
+    let facts = xbrl.facts();
+    let text = |name: &str| {
+        facts.iter()
+            .find(|f| f.name == name && !f.is_nil())
+            .and_then(|f| f.value.as_deref())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+    let date = |name: &str| text(name).and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok());
+
+    DocumentEntityInfo {
+        edinet_code: text("jpdei_cor:EDINETCodeDEI").map(str::to_string),
+        security_code: text("jpdei_cor:SecurityCodeDEI").map(str::to_string),
+        filer_name_en: text("jpdei_cor:FilerNameInEnglishDEI").map(str::to_string),
+        document_type: text("jpdei_cor:DocumentTypeDEI").map(str::to_string),
+        accounting_standard: text("jpdei_cor:AccountingStandardsDEI").and_then(AccountingStandard::parse),
+        consolidated: match text("jpdei_cor:WhetherConsolidatedFinancialStatementsArePreparedDEI") {
+            Some("true") => Some(true),
+            Some("false") => Some(false),
+            _ => None,
+        },
+        fiscal_year_start: date("jpdei_cor:CurrentFiscalYearStartDateDEI"),
+        fiscal_year_end: date("jpdei_cor:CurrentFiscalYearEndDateDEI"),
+        current_period_end: date("jpdei_cor:CurrentPeriodEndDateDEI"),
+        type_of_current_period: text("jpdei_cor:TypeOfCurrentPeriodDEI").map(str::to_string),
+        consolidated_industry_code: text("jpdei_cor:IndustryCodeWhenConsolidatedFinancialStatementsArePreparedInAccordanceWithIndustrySpecificRegulationsDEI")
+            .map(str::to_string),
+        industry_code: text("jpdei_cor:IndustryCodeWhenFinancialStatementsArePreparedInAccordanceWithIndustrySpecificRegulationsDEI")
+            .map(str::to_string),
+    }
+}
+
+/// Reads the statement of changes in equity: current-period facts broken
+/// down by an equity component axis, keyed by component member and line
+/// (the concept, as an opening balance, movement or closing balance), for
+/// either the consolidated or the non-consolidated statement. The totals of
+/// the same lines, which carry no component, are filed under `total_member`,
+/// the axis' default member.
+fn extract_changes_in_equity(xbrl: &DynamicXBRLContent, consolidated: bool, total_member: Option<&str>) -> ChangesInEquityStatement {
+    let mut stmt = ChangesInEquityStatement::default();
+
+    let Some(Period::Duration { start, end }) = xbrl.current_duration() else {
+        return stmt;
+    };
+    let line_kind = |period: &Period| match period {
+        Period::Duration { start: s, end: e } if (*s, *e) == (start, end) => Some(EquityLineKind::Movement),
+        Period::Instant(date) if *date == start - ChronoDuration::days(1) => Some(EquityLineKind::OpeningBalance),
+        Period::Instant(date) if *date == end => Some(EquityLineKind::ClosingBalance),
+        _ => None,
+    };
+
+    // Each fact with its component column, `None` being the total column.
+    let facts: Vec<(&XBRLElement, Option<&str>, EquityLineKind, f64)> = xbrl.facts()
+        .into_iter()
+        .filter(|f| f.unit_kind() == Some(UnitKind::Monetary))
+        .filter_map(|f| {
+            let context = xbrl.contexts.get(f.context_ref.as_ref()?)?;
+            let kind = line_kind(&context.period)?;
+            let value = f.numeric_value()?.value?;
+
+            let (consolidation, others): (Vec<&DimensionMember>, Vec<&DimensionMember>) = context.dimensions()
+                .partition(|d| d.dimension == CONSOLIDATION_AXIS);
+            let non_consolidated = consolidation.iter().any(|d| d.member == NON_CONSOLIDATED_MEMBER);
+            if non_consolidated == consolidated {
+                return None;
+            }
+
+            match others.as_slice() {
+                [] => Some((f, None, kind, value)),
+                [d] if EQUITY_COMPONENT_AXES.contains(&d.dimension.as_str()) => Some((f, Some(d.member.as_str()), kind, value)),
+                _ => None,
+            }
+        })
+        .collect();
+
+    for (fact, component, kind, value) in &facts {
+        if let Some(component) = component {
+            stmt.changes.push(EquityChange {
+                component: component.to_string(),
+                kind: *kind,
+                concept: fact.name.clone(),
+                value: *value,
+            });
         }
     }
 
-    for e in &xbrl.elements {
-        visit_element(e, &mut sheet);
+    if let Some(total_member) = total_member {
+        let mut line_concepts: Vec<String> = stmt.changes.iter().map(|c| c.concept.clone()).collect();
+        line_concepts.sort();
+        line_concepts.dedup();
+
+        for (fact, component, kind, value) in &facts {
+            if component.is_none() && line_concepts.binary_search(&fact.name).is_ok() {
+                stmt.changes.push(EquityChange {
+                    component: total_member.to_string(),
+                    kind: *kind,
+                    concept: fact.name.clone(),
+                    value: *value,
+                });
+            }
+        }
     }
 
-    sheet
+    stmt
 }
 
 #[tokio::main]
@@ -142,11 +414,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let base_dir = PathBuf::from("edinet_documents");
     fs::create_dir_all(&base_dir)?;
 
+    // Standard EDINET taxonomy schemas, as unpacked from the taxonomy download.
+    let taxonomy_dir = env::var("TAXONOMY_CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| base_dir.join("taxonomy"));
+
+    // Which concepts fill each field of the normalized statements.
+    let mappings_path = env::var("CONCEPT_MAPPINGS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("concept_mappings.json"));
+    let mappings = &load_concept_mappings(&mappings_path)?;
+
     let db_path = base_dir.join("reports.db");
     let conn = Connection::open(&db_path)?;
 
-    conn.execute_batch(
-        r#"
+    let schema = r#"
         PRAGMA foreign_keys = ON;
 
         CREATE TABLE IF NOT EXISTS quarterly_reports (
@@ -157,11 +439,27 @@ async fn main() -> Result<(), Box<dyn Error>> {
             submit_date_time TEXT,
             edinet_code TEXT,
             filer_name TEXT,
-            xbrl_zip_path TEXT
+            xbrl_zip_path TEXT,
+            accounting_standard TEXT,
+            primary_consolidated INTEGER,
+            filer_name_en TEXT,
+            document_type TEXT,
+            fiscal_year_start TEXT,
+            fiscal_year_end TEXT,
+            current_period_end TEXT,
+            type_of_current_period TEXT,
+            document_form TEXT,
+            parent_doc_id TEXT,
+            original_doc_id TEXT,
+            consolidated_industry_code TEXT,
+            industry_code TEXT
         );
 
         CREATE TABLE IF NOT EXISTS income_statements (
-            doc_id TEXT PRIMARY KEY,
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
             net_sales REAL,
             cost_of_sales REAL,
             gross_profit REAL,
@@ -188,29 +486,493 @@ async fn main() -> Result<(), Box<dyn Error>> {
             income_taxes REAL,
             income_before_minority_interests REAL,
             net_income REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
             FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
         );
 
         CREATE TABLE IF NOT EXISTS balance_sheets (
-            doc_id TEXT PRIMARY KEY,
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_end TEXT,
+            current_assets REAL,
             cash_and_deposits REAL,
             notes_and_accounts_receivable_trade REAL,
+            notes_receivable_trade REAL,
+            accounts_receivable_trade REAL,
+            electronically_recorded_monetary_claims_operating REAL,
             short_term_investment_securities REAL,
             merchandise REAL,
+            finished_goods REAL,
+            merchandise_and_finished_goods REAL,
+            work_in_process REAL,
+            raw_materials_and_supplies REAL,
+            inventories REAL,
+            short_term_loans_receivable REAL,
+            deferred_tax_assets_current REAL,
+            other_current_assets REAL,
+            allowance_for_doubtful_accounts_current REAL,
+            noncurrent_assets REAL,
             property_plant_and_equipment REAL,
+            buildings_and_structures_net REAL,
+            machinery_equipment_and_vehicles_net REAL,
+            land REAL,
+            lease_assets_net REAL,
+            construction_in_progress REAL,
             intangible_assets REAL,
+            goodwill REAL,
+            software REAL,
             investments_and_other_assets REAL,
+            investment_securities REAL,
+            long_term_loans_receivable REAL,
+            lease_and_guarantee_deposits REAL,
+            net_defined_benefit_asset REAL,
+            deferred_tax_assets REAL,
+            allowance_for_doubtful_accounts_noncurrent REAL,
+            deferred_assets REAL,
             total_assets REAL,
             current_liabilities REAL,
+            notes_and_accounts_payable_trade REAL,
+            accounts_payable_trade REAL,
+            electronically_recorded_obligations_operating REAL,
+            short_term_loans_payable REAL,
+            commercial_papers REAL,
+            current_portion_of_bonds REAL,
+            current_portion_of_long_term_loans_payable REAL,
+            lease_obligations_current REAL,
+            accounts_payable_other REAL,
+            income_taxes_payable REAL,
+            provision_for_bonuses REAL,
+            deferred_tax_liabilities_current REAL,
+            other_current_liabilities REAL,
             noncurrent_liabilities REAL,
+            bonds_payable REAL,
+            long_term_loans_payable REAL,
+            lease_obligations_noncurrent REAL,
+            deferred_tax_liabilities REAL,
+            provision_for_retirement_benefits REAL,
+            net_defined_benefit_liability REAL,
+            asset_retirement_obligations REAL,
+            other_noncurrent_liabilities REAL,
             total_liabilities REAL,
+            capital_stock REAL,
+            capital_surplus REAL,
+            retained_earnings REAL,
+            treasury_stock REAL,
             shareholders_equity REAL,
+            valuation_difference_on_available_for_sale_securities REAL,
+            deferred_gains_or_losses_on_hedges REAL,
+            foreign_currency_translation_adjustment REAL,
+            remeasurements_of_defined_benefit_plans REAL,
             valuation_and_translation_adjustments REAL,
+            subscription_rights_to_shares REAL,
+            non_controlling_interests REAL,
             total_equity REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
             FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
         );
-        "#
-    )?;
+
+        CREATE TABLE IF NOT EXISTS cash_flow_statements (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            income_before_income_taxes REAL,
+            depreciation_and_amortization REAL,
+            impairment_loss REAL,
+            amortization_of_goodwill REAL,
+            decrease_increase_in_notes_and_accounts_receivable_trade REAL,
+            decrease_increase_in_inventories REAL,
+            increase_decrease_in_notes_and_accounts_payable_trade REAL,
+            subtotal_operating REAL,
+            income_taxes_paid REAL,
+            net_cash_operating REAL,
+            purchase_of_property_plant_and_equipment REAL,
+            proceeds_from_sales_of_property_plant_and_equipment REAL,
+            purchase_of_intangible_assets REAL,
+            purchase_of_investment_securities REAL,
+            proceeds_from_sales_of_investment_securities REAL,
+            net_cash_investing REAL,
+            net_increase_decrease_in_short_term_loans_payable REAL,
+            proceeds_from_long_term_loans_payable REAL,
+            repayment_of_long_term_loans_payable REAL,
+            proceeds_from_issuance_of_bonds REAL,
+            redemption_of_bonds REAL,
+            purchase_of_treasury_stock REAL,
+            cash_dividends_paid REAL,
+            net_cash_financing REAL,
+            effect_of_exchange_rate_change REAL,
+            net_increase_decrease_in_cash REAL,
+            cash_and_cash_equivalents_beginning REAL,
+            cash_and_cash_equivalents_end REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS bank_income_statements (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            ordinary_income REAL,
+            interest_income REAL,
+            interest_on_loans_and_discounts REAL,
+            interest_and_dividends_on_securities REAL,
+            trust_fees REAL,
+            fees_and_commissions_income REAL,
+            trading_income REAL,
+            other_operating_income REAL,
+            other_ordinary_income REAL,
+            ordinary_expenses REAL,
+            interest_expenses REAL,
+            interest_on_deposits REAL,
+            fees_and_commissions_expenses REAL,
+            other_operating_expenses REAL,
+            general_and_administrative_expenses REAL,
+            other_ordinary_expenses REAL,
+            ordinary_profit REAL,
+            extraordinary_income REAL,
+            extraordinary_loss REAL,
+            income_before_income_taxes REAL,
+            income_taxes REAL,
+            net_income REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS bank_balance_sheets (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_end TEXT,
+            cash_and_due_from_banks REAL,
+            call_loans_and_bills_bought REAL,
+            monetary_claims_bought REAL,
+            trading_assets REAL,
+            money_held_in_trust REAL,
+            securities REAL,
+            loans_and_bills_discounted REAL,
+            foreign_exchanges REAL,
+            customers_liabilities_for_acceptances_and_guarantees REAL,
+            allowance_for_loan_losses REAL,
+            total_assets REAL,
+            deposits REAL,
+            negotiable_certificates_of_deposit REAL,
+            call_money_and_bills_sold REAL,
+            payables_under_repurchase_agreements REAL,
+            trading_liabilities REAL,
+            borrowed_money REAL,
+            foreign_exchanges_liabilities REAL,
+            bonds_payable REAL,
+            acceptances_and_guarantees REAL,
+            total_liabilities REAL,
+            shareholders_equity REAL,
+            valuation_and_translation_adjustments REAL,
+            total_equity REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS insurance_income_statements (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            ordinary_income REAL,
+            insurance_premiums REAL,
+            investment_income REAL,
+            other_ordinary_income REAL,
+            ordinary_expenses REAL,
+            insurance_claims REAL,
+            provision_for_policy_reserves REAL,
+            investment_expenses REAL,
+            operating_expenses REAL,
+            other_ordinary_expenses REAL,
+            ordinary_profit REAL,
+            extraordinary_income REAL,
+            extraordinary_loss REAL,
+            income_before_income_taxes REAL,
+            income_taxes REAL,
+            net_income REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS insurance_balance_sheets (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_end TEXT,
+            cash_and_deposits REAL,
+            call_loans REAL,
+            monetary_claims_bought REAL,
+            money_held_in_trust REAL,
+            securities REAL,
+            loans REAL,
+            tangible_fixed_assets REAL,
+            intangible_fixed_assets REAL,
+            total_assets REAL,
+            policy_reserves REAL,
+            reserve_for_outstanding_claims REAL,
+            underwriting_reserves REAL,
+            bonds_payable REAL,
+            total_liabilities REAL,
+            shareholders_equity REAL,
+            valuation_and_translation_adjustments REAL,
+            total_equity REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS securities_income_statements (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            operating_revenue REAL,
+            commissions_received REAL,
+            net_trading_income REAL,
+            financial_revenue REAL,
+            financial_expenses REAL,
+            net_operating_revenue REAL,
+            selling_general_admin REAL,
+            operating_income REAL,
+            ordinary_income REAL,
+            extraordinary_income REAL,
+            extraordinary_loss REAL,
+            income_before_income_taxes REAL,
+            income_taxes REAL,
+            net_income REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS securities_balance_sheets (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_end TEXT,
+            cash_and_deposits REAL,
+            cash_segregated_as_deposits REAL,
+            trading_products REAL,
+            margin_transaction_assets REAL,
+            loans_secured_by_securities REAL,
+            current_assets REAL,
+            total_assets REAL,
+            trading_products_liabilities REAL,
+            margin_transaction_liabilities REAL,
+            loans_payable_secured_by_securities REAL,
+            deposits_received REAL,
+            guarantee_deposits_received REAL,
+            short_term_loans_payable REAL,
+            current_liabilities REAL,
+            total_liabilities REAL,
+            shareholders_equity REAL,
+            valuation_and_translation_adjustments REAL,
+            total_equity REAL,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS per_share_data (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            basic_eps REAL,
+            diluted_eps REAL,
+            dividends_per_share REAL,
+            book_value_per_share REAL,
+            issued_shares REAL,
+            treasury_shares REAL,
+            currency TEXT,
+            PRIMARY KEY(doc_id, consolidated, period_end),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS segment_values (
+            doc_id TEXT NOT NULL,
+            segment TEXT NOT NULL,
+            segment_label TEXT,
+            concept TEXT NOT NULL,
+            period_start TEXT,
+            period_end TEXT NOT NULL,
+            unit_ref TEXT,
+            value REAL NOT NULL,
+            PRIMARY KEY(doc_id, segment, concept, period_end, period_start),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS text_blocks (
+            doc_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            context_ref TEXT NOT NULL,
+            text TEXT NOT NULL,
+            html TEXT NOT NULL,
+            PRIMARY KEY(doc_id, concept, context_ref),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS changes_in_equity (
+            doc_id TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            component TEXT NOT NULL,
+            line_kind TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            value REAL NOT NULL,
+            PRIMARY KEY(doc_id, consolidated, component, line_kind, concept),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS concept_labels (
+            doc_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            label_ja TEXT,
+            terse_label_ja TEXT,
+            verbose_label_ja TEXT,
+            label_en TEXT,
+            terse_label_en TEXT,
+            verbose_label_en TEXT,
+            PRIMARY KEY(doc_id, concept),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS concept_definitions (
+            doc_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            period_type TEXT,
+            balance TEXT,
+            data_type TEXT,
+            substitution_group TEXT,
+            is_abstract INTEGER NOT NULL,
+            nillable INTEGER NOT NULL,
+            PRIMARY KEY(doc_id, concept),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS statement_line_items (
+            doc_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            line_number INTEGER NOT NULL,
+            statement_kind TEXT NOT NULL,
+            consolidated INTEGER NOT NULL,
+            concept TEXT NOT NULL,
+            depth INTEGER NOT NULL,
+            preferred_label TEXT,
+            label TEXT,
+            value REAL,
+            prior_value REAL,
+            PRIMARY KEY(doc_id, role, line_number),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS calculation_inconsistencies (
+            doc_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            context_ref TEXT NOT NULL,
+            reported_value REAL NOT NULL,
+            computed_value REAL NOT NULL,
+            decimals INTEGER,
+            PRIMARY KEY(doc_id, role, concept, context_ref),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS dimension_members (
+            doc_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            hypercube TEXT NOT NULL,
+            dimension TEXT NOT NULL,
+            member TEXT NOT NULL,
+            parent_member TEXT,
+            is_default INTEGER NOT NULL,
+            label TEXT,
+            PRIMARY KEY(doc_id, role, hypercube, dimension, member),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE TABLE IF NOT EXISTS facts (
+            doc_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            context_ref TEXT NOT NULL,
+            period_start TEXT,
+            period_end TEXT,
+            period_instant TEXT,
+            dimensions TEXT,
+            unit_ref TEXT NOT NULL,
+            unit TEXT,
+            decimals TEXT,
+            value REAL,
+            PRIMARY KEY(doc_id, concept, context_ref),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS facts_concept ON facts(concept);
+
+        CREATE TABLE IF NOT EXISTS dimensional_facts (
+            doc_id TEXT NOT NULL,
+            concept TEXT NOT NULL,
+            context_ref TEXT NOT NULL,
+            dimension TEXT NOT NULL,
+            member TEXT NOT NULL,
+            unit_ref TEXT,
+            value REAL,
+            PRIMARY KEY(doc_id, concept, context_ref, dimension),
+            FOREIGN KEY(doc_id) REFERENCES quarterly_reports(doc_id)
+        );
+        "#;
+    conn.execute_batch(schema)?;
+    add_column_if_missing(&conn, "quarterly_reports", "accounting_standard", "TEXT")?;
+    add_column_if_missing(&conn, "quarterly_reports", "primary_consolidated", "INTEGER")?;
+    add_column_if_missing(&conn, "statement_line_items", "prior_value", "REAL")?;
+    for column in ["filer_name_en", "document_type", "fiscal_year_start", "fiscal_year_end", "current_period_end", "type_of_current_period", "document_form", "parent_doc_id", "original_doc_id", "consolidated_industry_code", "industry_code"] {
+        add_column_if_missing(&conn, "quarterly_reports", column, "TEXT")?;
+    }
+    // Statement fields added since the tables were created.
+    for (table, fields) in [
+        ("income_statements", field_values(&IncomeStatement::default())),
+        ("balance_sheets", field_values(&BalanceSheet::default())),
+        ("cash_flow_statements", field_values(&CashFlowStatement::default())),
+        ("bank_income_statements", field_values(&BankIncomeStatement::default())),
+        ("bank_balance_sheets", field_values(&BankBalanceSheet::default())),
+        ("insurance_income_statements", field_values(&InsuranceIncomeStatement::default())),
+        ("insurance_balance_sheets", field_values(&InsuranceBalanceSheet::default())),
+        ("securities_income_statements", field_values(&SecuritiesIncomeStatement::default())),
+        ("securities_balance_sheets", field_values(&SecuritiesBalanceSheet::default())),
+    ] {
+        for (field, _) in fields {
+            add_column_if_missing(&conn, table, column_name(&field), "REAL")?;
+        }
+    }
+
+    let duration_key = [("consolidated", "1"), ("period_start", "NULL"), ("period_end", "NULL")];
+    rekey_table(&conn, "income_statements", schema, &duration_key)?;
+    rekey_table(&conn, "balance_sheets", schema, &[("consolidated", "1"), ("period_end", "NULL")])?;
+    rekey_table(&conn, "cash_flow_statements", schema, &duration_key)?;
+    rekey_table(&conn, "changes_in_equity", schema, &[("consolidated", "1")])?;
+
+    // The figures of each report as last corrected: for every statement
+    // period of an original report and its amendments, the row from the most
+    // recently submitted document that has one. Re-created on every start
+    // since views are not rewritten when their tables are rekeyed.
+    let mut views = String::new();
+    for table in STATEMENT_TABLES {
+        views.push_str(&format!(
+            "DROP VIEW IF EXISTS effective_{table};
+            CREATE VIEW effective_{table} AS
+            SELECT * FROM (
+                SELECT r.edinet_code, COALESCE(r.original_doc_id, r.doc_id) AS original_doc_id, s.*,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY COALESCE(r.original_doc_id, r.doc_id), s.consolidated, s.period_end
+                        ORDER BY r.submit_date_time DESC
+                    ) AS revision_rank
+                FROM {table} s
+                JOIN quarterly_reports r ON r.doc_id = s.doc_id
+            )
+            WHERE revision_rank = 1;
+            "
+        ));
+    }
+    conn.execute_batch(&views)?;
+
+    // Standard taxonomy labels, parsed once for every filing that uses them.
+    let mut standard_labels = HashMap::new();
 
     let mut current_date = start_date;
     while current_date < end_date {
@@ -220,31 +982,168 @@ async fn main() -> Result<(), Box<dyn Error>> {
             Ok(api_resp) if api_resp.metadata.status == "200" => {
                 for doc in api_resp.results {
                     if let Some(code) = &doc.doc_type_code {
-                        if code == "140" || code == "150" {
+                        if is_supported_doc_type(code) {
                             let doc_id = doc.doc_id.clone();
 
                             let xbrl_zip_path = api_client.download_xbrl(&doc_id, &base_dir)
                                 .await
                                 .unwrap_or(None);
                             
-                            let xbrl_content = if let Some(path) = &xbrl_zip_path {
-                                let full_path = base_dir.join(path);
-                                extract_xbrl_from_zip(full_path.to_str().unwrap())
-                                    .and_then(|c| parse_dynamic_xbrl(&c))
-                                    .ok()
-                            } else {
-                                None
-                            };
+                            let mut archive = xbrl_zip_path.as_ref()
+                                .and_then(|path| XBRLArchive::open(&base_dir.join(path)).ok());
+                            let xbrl_content = archive.as_mut().and_then(|a| load_public_xbrl(a).ok());
+                            let form = archive.as_ref()
+                                .and_then(|a| a.document(DocumentKind::Public))
+                                .and_then(|d| d.instance.as_deref().or(d.ixbrl.first().map(String::as_str)))
+                                .and_then(form_of_file);
+                            let presentation = archive.as_mut()
+                                .and_then(|a| load_presentation(a, DocumentKind::Public).ok())
+                                .unwrap_or_default();
+                            let calculation = archive.as_mut()
+                                .and_then(|a| load_calculation(a, DocumentKind::Public).ok())
+                                .unwrap_or_default();
+                            let taxonomy = archive.as_mut()
+                                .and_then(|a| load_taxonomy(a, DocumentKind::Public, &taxonomy_dir).ok())
+                                .unwrap_or_default();
+                            let hypercubes: Vec<Hypercube> = archive.as_mut()
+                                .and_then(|a| load_definition(a, DocumentKind::Public).ok())
+                                .unwrap_or_default()
+                                .iter()
+                                .flat_map(|network| network.hypercubes())
+                                .collect();
+                            let concepts = referenced_concepts(xbrl_content.as_ref(), &[&presentation, &calculation], &hypercubes);
+                            let labels = archive.as_mut()
+                                .and_then(|a| load_labels(a, DocumentKind::Public, &taxonomy, &concepts, &mut standard_labels).ok())
+                                .unwrap_or_default();
 
-                            let income_statement = xbrl_content.as_ref().map(|xbrl| extract_income_statement(xbrl));
-                            let balance_sheet = xbrl_content.as_ref().map(|xbrl| extract_balance_sheet(xbrl));
+                            let dei = xbrl_content.as_ref().map(extract_document_entity_info);
+                            // An amendment's original is its parent's original, or the
+                            // parent itself when that is the original or was not ingested.
+                            let original_doc_id: String = match &doc.parent_doc_id {
+                                Some(parent) => conn.query_row(
+                                    "SELECT original_doc_id FROM quarterly_reports WHERE doc_id = ?1",
+                                    params![parent],
+                                    |row| row.get::<_, Option<String>>(0),
+                                ).ok().flatten().unwrap_or_else(|| parent.clone()),
+                                None => doc_id.clone(),
+                            };
+                            // Filings that do not declare a standard follow Japanese GAAP.
+                            let accounting_standard = dei.as_ref().map(|d| d.accounting_standard.unwrap_or_default());
+                            // Consolidated statements first, then the filer's standalone ones.
+                            // Scopes the filing does not report are left out. Industry
+                            // layouts only exist for Japanese GAAP statements.
+                            let scopes: Vec<Scope> = xbrl_content.as_ref()
+                                .zip(accounting_standard)
+                                .zip(dei.as_ref())
+                                .map(|((xbrl, standard), dei)| [true, false].map(|c| {
+                                    let standard = standard.for_statements(c);
+                                    let industry = match standard {
+                                        AccountingStandard::JapanGaap => statement_industry(xbrl, dei, c),
+                                        _ => Industry::General,
+                                    };
+                                    (xbrl, standard, industry, c)
+                                }).to_vec())
+                                .unwrap_or_default();
+                            // The current period and its comparatives, latest first.
+                            // The form's own period contexts, when it is one we know.
+                            let current_duration = xbrl_content.as_ref().and_then(|x| {
+                                form.and_then(|f| x.period_of(f.duration_context)).or_else(|| x.current_duration())
+                            });
+                            let current_instant = xbrl_content.as_ref().and_then(|x| {
+                                form.and_then(|f| x.period_of(f.instant_context))
+                                    .or(current_duration)
+                                    .and_then(|p| p.end_date())
+                            });
+                            let durations = xbrl_content.as_ref().zip(current_duration)
+                                .map(|(x, current)| x.reporting_durations(current))
+                                .unwrap_or_default();
+                            // The year-to-date period starts the day after the prior year-end.
+                            let prior_year_end = current_duration
+                                .and_then(|p| p.start_date())
+                                .map(|start| start - ChronoDuration::days(1));
+                            let instants = xbrl_content.as_ref().zip(current_instant)
+                                .map(|(x, current)| x.balance_sheet_instants(current, prior_year_end))
+                                .unwrap_or_default();
+                            // Financial industries have income statements and balance
+                            // sheets of their own; cash flow statements share one layout.
+                            let general = Some(Industry::General);
+                            let income_statements: Vec<(bool, Period, IncomeStatement)> =
+                                extract_statements(&scopes, general, &durations, &mappings.income_statement)?;
+                            let balance_sheets: Vec<(bool, Period, BalanceSheet)> =
+                                extract_statements(&scopes, general, &instants, &mappings.balance_sheet)?;
+                            let cash_flow_statements: Vec<(bool, Period, CashFlowStatement)> =
+                                extract_statements(&scopes, None, &durations, &mappings.cash_flow_statement)?;
+                            let bank = Some(Industry::Bank);
+                            let bank_income_statements: Vec<(bool, Period, BankIncomeStatement)> =
+                                extract_statements(&scopes, bank, &durations, &mappings.bank_income_statement)?;
+                            let bank_balance_sheets: Vec<(bool, Period, BankBalanceSheet)> =
+                                extract_statements(&scopes, bank, &instants, &mappings.bank_balance_sheet)?;
+                            let insurance = Some(Industry::Insurance);
+                            let insurance_income_statements: Vec<(bool, Period, InsuranceIncomeStatement)> =
+                                extract_statements(&scopes, insurance, &durations, &mappings.insurance_income_statement)?;
+                            let insurance_balance_sheets: Vec<(bool, Period, InsuranceBalanceSheet)> =
+                                extract_statements(&scopes, insurance, &instants, &mappings.insurance_balance_sheet)?;
+                            let securities = Some(Industry::Securities);
+                            let securities_income_statements: Vec<(bool, Period, SecuritiesIncomeStatement)> =
+                                extract_statements(&scopes, securities, &durations, &mappings.securities_income_statement)?;
+                            let securities_balance_sheets: Vec<(bool, Period, SecuritiesBalanceSheet)> =
+                                extract_statements(&scopes, securities, &instants, &mappings.securities_balance_sheet)?;
+                            let stated_scopes: HashSet<bool> = income_statements.iter().map(|s| s.0)
+                                .chain(balance_sheets.iter().map(|s| s.0))
+                                .chain(cash_flow_statements.iter().map(|s| s.0))
+                                .chain(bank_income_statements.iter().map(|s| s.0))
+                                .chain(bank_balance_sheets.iter().map(|s| s.0))
+                                .chain(insurance_income_statements.iter().map(|s| s.0))
+                                .chain(insurance_balance_sheets.iter().map(|s| s.0))
+                                .chain(securities_income_statements.iter().map(|s| s.0))
+                                .chain(securities_balance_sheets.iter().map(|s| s.0))
+                                .collect();
+                            let per_share_data = dei.as_ref()
+                                .map(|dei| extract_per_share_rows(&scopes, &durations, &stated_scopes, dei))
+                                .unwrap_or_default();
+                            let text_blocks = xbrl_content.as_ref()
+                                .map(|xbrl| extract_text_blocks(xbrl, &taxonomy))
+                                .unwrap_or_default();
+                            let segment_values = xbrl_content.as_ref()
+                                .map(extract_segment_values)
+                                .unwrap_or_default();
+                            let numeric_facts = xbrl_content.as_ref()
+                                .map(extract_numeric_facts)
+                                .unwrap_or_default();
+                            let equity_total_member = hypercubes.iter()
+                                .flat_map(|h| &h.dimensions)
+                                .find(|d| EQUITY_COMPONENT_AXES.contains(&d.axis.as_str()))
+                                .and_then(|d| d.default.as_deref());
+                            let changes_in_equity: Vec<(bool, ChangesInEquityStatement)> = scopes.iter()
+                                .map(|&(xbrl, _, _, c)| (c, extract_changes_in_equity(xbrl, c, equity_total_member)))
+                                .collect();
+                            let primary_consolidated = dei.as_ref().map(|dei| {
+                                dei.consolidated.unwrap_or_else(|| {
+                                    income_statements.iter().any(|(c, _, _)| *c)
+                                        || balance_sheets.iter().any(|(c, _, _)| *c)
+                                        || bank_balance_sheets.iter().any(|(c, _, _)| *c)
+                                        || insurance_balance_sheets.iter().any(|(c, _, _)| *c)
+                                        || securities_balance_sheets.iter().any(|(c, _, _)| *c)
+                                })
+                            });
+                            let presented_statements = xbrl_content.as_ref()
+                                .map(|xbrl| build_presented_statements(xbrl, &presentation, &labels, &taxonomy))
+                                .unwrap_or_default();
+                            let inconsistencies = xbrl_content.as_ref()
+                                .map(|xbrl| check_calculations(xbrl, &calculation))
+                                .unwrap_or_default();
 
                             conn.execute(
                                 "INSERT INTO quarterly_reports (
                                     doc_id, date, sec_code, doc_type_code,
                                     submit_date_time, edinet_code, filer_name,
-                                    xbrl_zip_path
-                                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                                    xbrl_zip_path, accounting_standard, primary_consolidated,
+                                    filer_name_en, document_type, fiscal_year_start,
+                                    fiscal_year_end, current_period_end, type_of_current_period,
+                                    document_form, parent_doc_id, original_doc_id,
+                                    consolidated_industry_code, industry_code
+                                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
+                                        ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)
                                 ON CONFLICT(doc_id) DO UPDATE SET
                                     date = ?2,
                                     sec_code = ?3,
@@ -252,146 +1151,362 @@ async fn main() -> Result<(), Box<dyn Error>> {
                                     submit_date_time = ?5,
                                     edinet_code = ?6,
                                     filer_name = ?7,
-                                    xbrl_zip_path = ?8",
+                                    xbrl_zip_path = ?8,
+                                    accounting_standard = ?9,
+                                    primary_consolidated = ?10,
+                                    filer_name_en = ?11,
+                                    document_type = ?12,
+                                    fiscal_year_start = ?13,
+                                    fiscal_year_end = ?14,
+                                    current_period_end = ?15,
+                                    type_of_current_period = ?16,
+                                    document_form = ?17,
+                                    parent_doc_id = ?18,
+                                    original_doc_id = ?19,
+                                    consolidated_industry_code = ?20,
+                                    industry_code = ?21",
                                 params![
                                     doc_id,
                                     date_str,
-                                    doc.sec_code,
+                                    doc.sec_code.clone().or_else(|| dei.as_ref()?.security_code.clone()),
                                     code,
                                     doc.submit_date_time,
-                                    doc.edinet_code,
+                                    doc.edinet_code.clone().or_else(|| dei.as_ref()?.edinet_code.clone()),
                                     doc.filer_name,
                                     xbrl_zip_path,
+                                    accounting_standard.map(|s| s.as_str()),
+                                    primary_consolidated,
+                                    dei.as_ref().and_then(|d| d.filer_name_en.clone()),
+                                    dei.as_ref().and_then(|d| d.document_type.clone()),
+                                    dei.as_ref().and_then(|d| d.fiscal_year_start).map(|d| d.to_string()),
+                                    dei.as_ref().and_then(|d| d.fiscal_year_end).map(|d| d.to_string()),
+                                    dei.as_ref().and_then(|d| d.current_period_end).map(|d| d.to_string()),
+                                    dei.as_ref().and_then(|d| d.type_of_current_period.clone()),
+                                    form.map(|f| f.taxonomy),
+                                    doc.parent_doc_id,
+                                    original_doc_id,
+                                    dei.as_ref().and_then(|d| d.consolidated_industry_code.clone()),
+                                    dei.as_ref().and_then(|d| d.industry_code.clone()),
                                 ],
                             )?;
 
-                            if let Some(stmt) = income_statement {
+                            for table in STATEMENT_TABLES {
+                                conn.execute(&format!("DELETE FROM {} WHERE doc_id = ?1", table), params![doc_id])?;
+                            }
+
+                            for (consolidated, period, stmt) in &income_statements {
+                                upsert_statement(&conn, "income_statements", &doc_id, *consolidated, period, stmt)?;
+                            }
+                            for (consolidated, period, sheet) in &balance_sheets {
+                                upsert_statement(&conn, "balance_sheets", &doc_id, *consolidated, period, sheet)?;
+                            }
+                            for (consolidated, period, stmt) in &cash_flow_statements {
+                                upsert_statement(&conn, "cash_flow_statements", &doc_id, *consolidated, period, stmt)?;
+                            }
+                            for (consolidated, period, stmt) in &bank_income_statements {
+                                upsert_statement(&conn, "bank_income_statements", &doc_id, *consolidated, period, stmt)?;
+                            }
+                            for (consolidated, period, sheet) in &bank_balance_sheets {
+                                upsert_statement(&conn, "bank_balance_sheets", &doc_id, *consolidated, period, sheet)?;
+                            }
+                            for (consolidated, period, stmt) in &insurance_income_statements {
+                                upsert_statement(&conn, "insurance_income_statements", &doc_id, *consolidated, period, stmt)?;
+                            }
+                            for (consolidated, period, sheet) in &insurance_balance_sheets {
+                                upsert_statement(&conn, "insurance_balance_sheets", &doc_id, *consolidated, period, sheet)?;
+                            }
+                            for (consolidated, period, stmt) in &securities_income_statements {
+                                upsert_statement(&conn, "securities_income_statements", &doc_id, *consolidated, period, stmt)?;
+                            }
+                            for (consolidated, period, sheet) in &securities_balance_sheets {
+                                upsert_statement(&conn, "securities_balance_sheets", &doc_id, *consolidated, period, sheet)?;
+                            }
+
+                            for (consolidated, period, data) in &per_share_data {
+                                conn.execute(
+                                    "INSERT INTO per_share_data (
+                                        doc_id, consolidated, period_start, period_end,
+                                        basic_eps, diluted_eps, dividends_per_share,
+                                        book_value_per_share, issued_shares, treasury_shares,
+                                        currency
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                                    ON CONFLICT(doc_id, consolidated, period_end) DO UPDATE SET
+                                        period_start = ?3,
+                                        basic_eps = ?5,
+                                        diluted_eps = ?6,
+                                        dividends_per_share = ?7,
+                                        book_value_per_share = ?8,
+                                        issued_shares = ?9,
+                                        treasury_shares = ?10,
+                                        currency = ?11",
+                                    params![
+                                        doc_id,
+                                        consolidated,
+                                        period.start_date().map(|d| d.to_string()),
+                                        period.end_date().map(|d| d.to_string()),
+                                        data.basic_eps,
+                                        data.diluted_eps,
+                                        data.dividends_per_share,
+                                        data.book_value_per_share,
+                                        data.issued_shares,
+                                        data.treasury_shares,
+                                        data.currency,
+                                    ],
+                                )?;
+                            }
+
+                            conn.execute(
+                                "DELETE FROM text_blocks WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for block in &text_blocks {
+                                conn.execute(
+                                    "INSERT INTO text_blocks (
+                                        doc_id, concept, context_ref, text, html
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5)
+                                    ON CONFLICT(doc_id, concept, context_ref) DO UPDATE SET
+                                        text = ?4,
+                                        html = ?5",
+                                    params![
+                                        doc_id,
+                                        block.concept,
+                                        block.context_ref,
+                                        block.text,
+                                        block.html,
+                                    ],
+                                )?;
+                            }
+
+                            conn.execute(
+                                "DELETE FROM segment_values WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for segment_value in &segment_values {
+                                conn.execute(
+                                    "INSERT INTO segment_values (
+                                        doc_id, segment, segment_label, concept,
+                                        period_start, period_end, unit_ref, value
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                                    ON CONFLICT(doc_id, segment, concept, period_end, period_start) DO UPDATE SET
+                                        segment_label = ?3,
+                                        unit_ref = ?7,
+                                        value = ?8",
+                                    params![
+                                        doc_id,
+                                        segment_value.segment,
+                                        labels.label(&segment_value.segment, "ja", STANDARD_LABEL_ROLE),
+                                        segment_value.concept,
+                                        segment_value.period.start_date().map(|d| d.to_string()),
+                                        segment_value.period.end_date().map(|d| d.to_string()),
+                                        segment_value.unit_ref,
+                                        segment_value.value,
+                                    ],
+                                )?;
+                            }
+
+                            replace_facts(&conn, &doc_id, &numeric_facts)?;
+
+                            conn.execute(
+                                "DELETE FROM changes_in_equity WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for (consolidated, change) in changes_in_equity.iter().flat_map(|(c, stmt)| stmt.changes.iter().map(move |change| (c, change))) {
+                                conn.execute(
+                                    "INSERT INTO changes_in_equity (
+                                        doc_id, consolidated, component, line_kind, concept, value
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                                    ON CONFLICT(doc_id, consolidated, component, line_kind, concept) DO UPDATE SET
+                                        value = ?6",
+                                    params![
+                                        doc_id,
+                                        consolidated,
+                                        change.component,
+                                        change.kind.as_str(),
+                                        change.concept,
+                                        change.value,
+                                    ],
+                                )?;
+                            }
+
+                            for concept in labels.labels.keys() {
+                                conn.execute(
+                                    "INSERT INTO concept_labels (
+                                        doc_id, concept, label_ja, terse_label_ja,
+                                        verbose_label_ja, label_en, terse_label_en,
+                                        verbose_label_en
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                                    ON CONFLICT(doc_id, concept) DO UPDATE SET
+                                        label_ja = ?3,
+                                        terse_label_ja = ?4,
+                                        verbose_label_ja = ?5,
+                                        label_en = ?6,
+                                        terse_label_en = ?7,
+                                        verbose_label_en = ?8",
+                                    params![
+                                        doc_id,
+                                        concept,
+                                        labels.label(concept, "ja", STANDARD_LABEL_ROLE),
+                                        labels.label(concept, "ja", TERSE_LABEL_ROLE),
+                                        labels.label(concept, "ja", VERBOSE_LABEL_ROLE),
+                                        labels.label(concept, "en", STANDARD_LABEL_ROLE),
+                                        labels.label(concept, "en", TERSE_LABEL_ROLE),
+                                        labels.label(concept, "en", VERBOSE_LABEL_ROLE),
+                                    ],
+                                )?;
+                            }
+
+                            let mut defined_concepts: Vec<&str> = xbrl_content.as_ref()
+                                .map(|xbrl| xbrl.facts().into_iter().map(|f| f.name.as_str()).collect())
+                                .unwrap_or_default();
+                            defined_concepts.sort();
+                            defined_concepts.dedup();
+
+                            for definition in defined_concepts.iter().filter_map(|c| taxonomy.concept(c)) {
                                 conn.execute(
-                                    "INSERT INTO income_statements (
-                                        doc_id, net_sales, cost_of_sales, gross_profit,
-                                        selling_general_admin, operating_income,
-                                        interest_income_noi, dividends_income_noi,
-                                        interest_and_dividends_income_noi,
-                                        purchase_discounts_noi, rent_income_noi,
-                                        house_rent_income_noi, other_noi,
-                                        non_operating_income, sales_discounts_noe,
-                                        rent_cost_real_estate_noe, other_noe,
-                                        non_operating_expenses, ordinary_income,
-                                        gain_on_sales_of_noncurrent_assets_ei,
-                                        extraordinary_income, income_before_income_taxes,
-                                        income_taxes_current, income_taxes_deferred,
-                                        income_taxes, income_before_minority_interests,
-                                        net_income
-                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
-                                            ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19,
-                                            ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)
-                                    ON CONFLICT(doc_id) DO UPDATE SET
-                                        net_sales = ?2,
-                                        cost_of_sales = ?3,
-                                        gross_profit = ?4,
-                                        selling_general_admin = ?5,
-                                        operating_income = ?6,
-                                        interest_income_noi = ?7,
-                                        dividends_income_noi = ?8,
-                                        interest_and_dividends_income_noi = ?9,
-                                        purchase_discounts_noi = ?10,
-                                        rent_income_noi = ?11,
-                                        house_rent_income_noi = ?12,
-                                        other_noi = ?13,
-                                        non_operating_income = ?14,
-                                        sales_discounts_noe = ?15,
-                                        rent_cost_real_estate_noe = ?16,
-                                        other_noe = ?17,
-                                        non_operating_expenses = ?18,
-                                        ordinary_income = ?19,
-                                        gain_on_sales_of_noncurrent_assets_ei = ?20,
-                                        extraordinary_income = ?21,
-                                        income_before_income_taxes = ?22,
-                                        income_taxes_current = ?23,
-                                        income_taxes_deferred = ?24,
-                                        income_taxes = ?25,
-                                        income_before_minority_interests = ?26,
-                                        net_income = ?27",
+                                    "INSERT INTO concept_definitions (
+                                        doc_id, concept, period_type, balance,
+                                        data_type, substitution_group, is_abstract, nillable
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                                    ON CONFLICT(doc_id, concept) DO UPDATE SET
+                                        period_type = ?3,
+                                        balance = ?4,
+                                        data_type = ?5,
+                                        substitution_group = ?6,
+                                        is_abstract = ?7,
+                                        nillable = ?8",
                                     params![
                                         doc_id,
-                                        stmt.net_sales,
-                                        stmt.cost_of_sales,
-                                        stmt.gross_profit,
-                                        stmt.selling_general_admin,
-                                        stmt.operating_income,
-                                        stmt.interest_income_noi,
-                                        stmt.dividends_income_noi,
-                                        stmt.interest_and_dividends_income_noi,
-                                        stmt.purchase_discounts_noi,
-                                        stmt.rent_income_noi,
-                                        stmt.house_rent_income_noi,
-                                        stmt.other_noi,
-                                        stmt.non_operating_income,
-                                        stmt.sales_discounts_noe,
-                                        stmt.rent_cost_real_estate_noe,
-                                        stmt.other_noe,
-                                        stmt.non_operating_expenses,
-                                        stmt.ordinary_income,
-                                        stmt.gain_on_sales_of_noncurrent_assets_ei,
-                                        stmt.extraordinary_income,
-                                        stmt.income_before_income_taxes,
-                                        stmt.income_taxes_current,
-                                        stmt.income_taxes_deferred,
-                                        stmt.income_taxes,
-                                        stmt.income_before_minority_interests,
-                                        stmt.net_income,
+                                        definition.concept,
+                                        definition.period_type.map(|p| p.as_str()),
+                                        definition.balance.map(|b| b.as_str()),
+                                        definition.data_type,
+                                        definition.substitution_group,
+                                        definition.is_abstract,
+                                        definition.nillable,
                                     ],
                                 )?;
                             }
 
-                            if let Some(sheet) = balance_sheet {
+                            conn.execute(
+                                "DELETE FROM statement_line_items WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for statement in &presented_statements {
+                                for (line_number, item) in statement.line_items.iter().enumerate() {
+                                    conn.execute(
+                                        "INSERT INTO statement_line_items (
+                                            doc_id, role, line_number, statement_kind,
+                                            consolidated, concept, depth,
+                                            preferred_label, label, value, prior_value
+                                        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                                        params![
+                                            doc_id,
+                                            statement.role,
+                                            line_number,
+                                            statement.kind.as_str(),
+                                            statement.consolidated,
+                                            item.concept,
+                                            item.depth,
+                                            item.preferred_label,
+                                            item.label,
+                                            item.value,
+                                            item.prior_value,
+                                        ],
+                                    )?;
+                                }
+                            }
+
+                            conn.execute(
+                                "DELETE FROM calculation_inconsistencies WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for inconsistency in &inconsistencies {
                                 conn.execute(
-                                    "INSERT INTO balance_sheets (
-                                        doc_id, cash_and_deposits,
-                                        notes_and_accounts_receivable_trade,
-                                        short_term_investment_securities, merchandise,
-                                        property_plant_and_equipment, intangible_assets,
-                                        investments_and_other_assets, total_assets,
-                                        current_liabilities, noncurrent_liabilities,
-                                        total_liabilities, shareholders_equity,
-                                        valuation_and_translation_adjustments,
-                                        total_equity
-                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10,
-                                            ?11, ?12, ?13, ?14, ?15)
-                                    ON CONFLICT(doc_id) DO UPDATE SET
-                                        cash_and_deposits = ?2,
-                                        notes_and_accounts_receivable_trade = ?3,
-                                        short_term_investment_securities = ?4,
-                                        merchandise = ?5,
-                                        property_plant_and_equipment = ?6,
-                                        intangible_assets = ?7,
-                                        investments_and_other_assets = ?8,
-                                        total_assets = ?9,
-                                        current_liabilities = ?10,
-                                        noncurrent_liabilities = ?11,
-                                        total_liabilities = ?12,
-                                        shareholders_equity = ?13,
-                                        valuation_and_translation_adjustments = ?14,
-                                        total_equity = ?15",
+                                    "INSERT INTO calculation_inconsistencies (
+                                        doc_id, role, concept, context_ref,
+                                        reported_value, computed_value, decimals
+                                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
                                     params![
                                         doc_id,
-                                        sheet.assets.cash_and_deposits,
-                                        sheet.assets.notes_and_accounts_receivable_trade,
-                                        sheet.assets.short_term_investment_securities,
-                                        sheet.assets.merchandise,
-                                        sheet.assets.property_plant_and_equipment,
-                                        sheet.assets.intangible_assets,
-                                        sheet.assets.investments_and_other_assets,
-                                        sheet.assets.total_assets,
-                                        sheet.liabilities.current_liabilities,
-                                        sheet.liabilities.noncurrent_liabilities,
-                                        sheet.liabilities.total_liabilities,
-                                        sheet.equity.shareholders_equity,
-                                        sheet.equity.valuation_and_translation_adjustments,
-                                        sheet.equity.total_equity,
+                                        inconsistency.role,
+                                        inconsistency.concept,
+                                        inconsistency.context_ref,
+                                        inconsistency.reported,
+                                        inconsistency.computed,
+                                        inconsistency.decimals,
                                     ],
                                 )?;
                             }
+
+                            conn.execute(
+                                "DELETE FROM dimension_members WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+                            conn.execute(
+                                "DELETE FROM dimensional_facts WHERE doc_id = ?1",
+                                params![doc_id],
+                            )?;
+
+                            for hypercube in &hypercubes {
+                                for dimension in &hypercube.dimensions {
+                                    for member in &dimension.members {
+                                        conn.execute(
+                                            "INSERT INTO dimension_members (
+                                                doc_id, role, hypercube, dimension, member,
+                                                parent_member, is_default, label
+                                            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                                            ON CONFLICT(doc_id, role, hypercube, dimension, member) DO NOTHING",
+                                            params![
+                                                doc_id,
+                                                hypercube.role,
+                                                hypercube.table,
+                                                dimension.axis,
+                                                member.member,
+                                                member.parent,
+                                                dimension.default.as_ref() == Some(&member.member),
+                                                labels.label(&member.member, "ja", STANDARD_LABEL_ROLE),
+                                            ],
+                                        )?;
+
+                                        if dimension.default.as_ref() == Some(&member.member) {
+                                            continue;
+                                        }
+
+                                        let Some(xbrl) = &xbrl_content else {
+                                            continue;
+                                        };
+                                        for fact in xbrl.facts_for_member(&dimension.axis, &member.member) {
+                                            let Some(value) = fact.numeric_value().and_then(|f| f.value) else {
+                                                continue;
+                                            };
+                                            conn.execute(
+                                                "INSERT INTO dimensional_facts (
+                                                    doc_id, concept, context_ref, dimension,
+                                                    member, unit_ref, value
+                                                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                                                ON CONFLICT(doc_id, concept, context_ref, dimension) DO UPDATE SET
+                                                    member = ?5,
+                                                    unit_ref = ?6,
+                                                    value = ?7",
+                                                params![
+                                                    doc_id,
+                                                    fact.name,
+                                                    fact.context_ref,
+                                                    dimension.axis,
+                                                    member.member,
+                                                    fact.unit_ref,
+                                                    value,
+                                                ],
+                                            )?;
+                                        }
+                                    }
+                                }
+                            }
                         }
                     }
                 }
@@ -407,89 +1522,301 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn extract_xbrl_from_zip(zip_path: &str) -> Result<String, Box<dyn Error>> {
-    let file = File::open(zip_path)?;
-    let mut archive = ZipArchive::new(file)?;
+/// Adds a column introduced after a table was first created, so databases
+/// written by earlier versions keep working.
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt.query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
 
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        if file.name().ends_with(".xbrl") {
-            let mut content = String::new();
-            file.read_to_string(&mut content)?;
-            return Ok(content);
-        }
+    if !exists {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), [])?;
     }
+    Ok(())
+}
 
-    Err("No .xbrl file found in the zip archive".into())
+/// The column a statement field is stored in: its name without the section
+/// it is nested in (`assets.total_assets` is `total_assets`).
+fn column_name(field: &str) -> &str {
+    field.rsplit('.').next().unwrap_or(field)
 }
 
-fn parse_dynamic_xbrl(content: &str) -> Result<DynamicXBRLContent, Box<dyn Error>> {
-    let mut reader = Reader::from_str(content);
-    reader.trim_text(true);
-
-    let mut namespaces = HashMap::new();
-    let mut contexts = HashMap::new();
-    let mut elements = Vec::new();
-    let mut element_stack = Vec::new();
-
-    loop {
-        match reader.read_event() {
-            Ok(Event::Start(e)) => {
-                let name = String::from_utf8(e.name().as_ref().to_vec())?;
-
-                let mut attributes = HashMap::new();
-                let mut context_ref = None;
-                let mut unit_ref = None;
-
-                for attr in e.attributes() {
-                    let attr = attr?;
-                    let key = String::from_utf8(attr.key.as_ref().to_vec())?;
-                    let value = String::from_utf8(attr.value.to_vec())?;
-
-                    match key.as_str() {
-                        "contextRef" => context_ref = Some(value),
-                        "unitRef" => unit_ref = Some(value),
-                        _ => {
-                            if key.starts_with("xmlns:") {
-                                namespaces.insert(key[6..].to_string(), value.clone());
-                            }
-                            attributes.insert(key, value);
-                        }
-                    }
-                }
+/// Upserts one normalized statement of a filing, keyed by scope and period,
+/// with one column per field.
+fn upsert_statement<T: Serialize>(
+    conn: &Connection,
+    table: &str,
+    doc_id: &str,
+    consolidated: bool,
+    period: &Period,
+    stmt: &T,
+) -> rusqlite::Result<()> {
+    let mut columns = vec!["doc_id", "consolidated", "period_end"];
+    let mut values = vec![
+        SqlValue::from(doc_id.to_string()),
+        SqlValue::from(consolidated),
+        SqlValue::from(period.end_date().map(|d| d.to_string())),
+    ];
+    if let Some(start) = period.start_date() {
+        columns.push("period_start");
+        values.push(SqlValue::from(start.to_string()));
+    }
 
-                element_stack.push(XBRLElement {
-                    name,
-                    value: None,
-                    attributes,
-                    children: Vec::new(),
-                    context_ref,
-                    unit_ref,
-                });
-            },
-            Ok(Event::Text(e)) => {
-                if let Some(element) = element_stack.last_mut() {
-                    element.value = Some(String::from_utf8(e.to_vec())?);
-                }
-            },
-            Ok(Event::End(_)) => {
-                if let Some(element) = element_stack.pop() {
-                    if element_stack.is_empty() {
-                        elements.push(element);
-                    } else if let Some(parent) = element_stack.last_mut() {
-                        parent.children.push(element);
-                    }
-                }
-            },
-            Ok(Event::Eof) => break,
-            Err(e) => return Err(format!("Error parsing XBRL: {:?}", e).into()),
-            _ => (),
+    let fields = field_values(stmt);
+    for (field, value) in &fields {
+        columns.push(column_name(field));
+        values.push(SqlValue::from(value.as_f64()));
+    }
+
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let updates: Vec<String> = columns[3..].iter().map(|c| format!("{c} = excluded.{c}")).collect();
+    conn.execute(
+        &format!(
+            "INSERT INTO {} ({}) VALUES ({})
+            ON CONFLICT(doc_id, consolidated, period_end) DO UPDATE SET {}",
+            table,
+            columns.join(", "),
+            placeholders.join(", "),
+            updates.join(", "),
+        ),
+        params_from_iter(values),
+    )?;
+    Ok(())
+}
+
+/// Replaces the generic facts stored for a filing.
+fn replace_facts(conn: &Connection, doc_id: &str, facts: &[ReportedFact]) -> Result<(), Box<dyn Error>> {
+    // Filings carry thousands of facts; one transaction
+    // keeps this from committing row by row.
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "DELETE FROM facts WHERE doc_id = ?1",
+        params![doc_id],
+    )?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO facts (
+                doc_id, concept, context_ref, period_start, period_end,
+                period_instant, dimensions, unit_ref, unit, decimals, value
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ON CONFLICT(doc_id, concept, context_ref) DO UPDATE SET
+                period_start = ?4,
+                period_end = ?5,
+                period_instant = ?6,
+                dimensions = ?7,
+                unit_ref = ?8,
+                unit = ?9,
+                decimals = ?10,
+                value = ?11",
+        )?;
+        for fact in facts {
+            let (period_start, period_end, period_instant) = match fact.period {
+                Period::Duration { start, end } => (Some(start.to_string()), Some(end.to_string()), None),
+                Period::Instant(date) => (None, None, Some(date.to_string())),
+                Period::Forever => (None, None, None),
+            };
+            // Axis-to-member JSON object, sorted by axis.
+            let dimensions = (!fact.dimensions.is_empty()).then(|| {
+                let map: BTreeMap<&str, &str> = fact.dimensions.iter()
+                    .map(|d| (d.dimension.as_str(), d.member.as_str()))
+                    .collect();
+                serde_json::to_string(&map)
+            }).transpose()?;
+
+            insert.execute(params![
+                doc_id,
+                fact.concept,
+                fact.context_ref,
+                period_start,
+                period_end,
+                period_instant,
+                dimensions,
+                fact.unit_ref,
+                fact.unit,
+                fact.decimals.map(|d| d.to_string()),
+                fact.value,
+            ])?;
         }
     }
+    tx.commit()?;
+    Ok(())
+}
 
-    Ok(DynamicXBRLContent {
-        namespaces,
-        contexts,
-        elements,
-    })
-}
\ No newline at end of file
+/// Re-creates `table` from `schema` when it predates columns that were added
+/// to its key, copying the rows it held with `defaults` (column, SQL value)
+/// for the columns they lack. Rows from before non-consolidated statements
+/// were stored are consolidated; those from before comparatives were stored
+/// have no recorded period.
+fn rekey_table(conn: &Connection, table: &str, schema: &str, defaults: &[(&str, &str)]) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let columns: Vec<String> = stmt.query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .collect();
+    let missing: Vec<&(&str, &str)> = defaults.iter()
+        .filter(|(column, _)| !columns.iter().any(|c| c == column))
+        .collect();
+    if columns.is_empty() || missing.is_empty() {
+        return Ok(());
+    }
+
+    let target = columns.iter().map(String::as_str)
+        .chain(missing.iter().map(|(column, _)| *column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let source = columns.iter().map(String::as_str)
+        .chain(missing.iter().map(|(_, value)| *value))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    conn.execute_batch(&format!("ALTER TABLE {table} RENAME TO {table}_legacy;"))?;
+    conn.execute_batch(schema)?;
+    conn.execute_batch(&format!(
+        "INSERT INTO {table} ({target}) SELECT {source} FROM {table}_legacy;
+        DROP TABLE {table}_legacy;"
+    ))
+}
+
+/// Parses the filing's PublicDoc instance, falling back to its inline XBRL
+/// pages when the instance is missing or unreadable.
+fn load_public_xbrl(archive: &mut XBRLArchive) -> Result<DynamicXBRLContent, Box<dyn Error>> {
+    archive.read_instance(DocumentKind::Public)
+        .and_then(|c| parse_dynamic_xbrl(&c))
+        .or_else(|_| parse_inline_xbrl(&archive.read_ixbrl(DocumentKind::Public)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOURCES: &str = concat!(
+        r#"<ix:header><ix:resources>"#,
+        r#"<xbrli:context id="CurrentYearDuration"><xbrli:entity><xbrli:identifier scheme="http://disclosure.edinet-fsa.go.jp">E00000-000</xbrli:identifier></xbrli:entity>"#,
+        r#"<xbrli:period><xbrli:startDate>2023-04-01</xbrli:startDate><xbrli:endDate>2024-03-31</xbrli:endDate></xbrli:period></xbrli:context>"#,
+        r#"<xbrli:context id="CurrentYearInstant_NonConsolidatedMember"><xbrli:entity><xbrli:identifier scheme="http://disclosure.edinet-fsa.go.jp">E00000-000</xbrli:identifier>"#,
+        r#"<xbrli:segment><xbrldi:explicitMember dimension="jppfs_cor:ConsolidatedOrNonConsolidatedAxis">jppfs_cor:NonConsolidatedMember</xbrldi:explicitMember></xbrli:segment></xbrli:entity>"#,
+        r#"<xbrli:period><xbrli:instant>2024-03-31</xbrli:instant></xbrli:period></xbrli:context>"#,
+        r#"<xbrli:context id="CurrentYearDuration_NonConsolidatedMember"><xbrli:entity><xbrli:identifier scheme="http://disclosure.edinet-fsa.go.jp">E00000-000</xbrli:identifier>"#,
+        r#"<xbrli:segment><xbrldi:explicitMember dimension="jppfs_cor:ConsolidatedOrNonConsolidatedAxis">jppfs_cor:NonConsolidatedMember</xbrldi:explicitMember></xbrli:segment></xbrli:entity>"#,
+        r#"<xbrli:period><xbrli:startDate>2023-04-01</xbrli:startDate><xbrli:endDate>2024-03-31</xbrli:endDate></xbrli:period></xbrli:context>"#,
+        r#"<xbrli:unit id="JPY"><xbrli:measure>iso4217:JPY</xbrli:measure></xbrli:unit>"#,
+        r#"<xbrli:unit id="JPYPerShares"><xbrli:divide><xbrli:unitNumerator><xbrli:measure>iso4217:JPY</xbrli:measure></xbrli:unitNumerator>"#,
+        r#"<xbrli:unitDenominator><xbrli:measure>xbrli:shares</xbrli:measure></xbrli:unitDenominator></xbrli:divide></xbrli:unit>"#,
+        r#"<xbrli:unit id="shares"><xbrli:measure>xbrli:shares</xbrli:measure></xbrli:unit>"#,
+        r#"</ix:resources></ix:header>"#,
+    );
+
+    fn page(body: &str) -> String {
+        format!(
+            concat!(
+                r#"<html xmlns:ix="http://www.xbrl.org/2013/inlineXBRL" xmlns:xbrli="http://www.xbrl.org/2003/instance" "#,
+                r#"xmlns:xbrldi="http://xbrl.org/2006/xbrldi"><body>{}</body></html>"#,
+            ),
+            body,
+        )
+    }
+
+    #[test]
+    fn stores_numeric_facts_once_across_pages() {
+        let net_sales = r#"<ix:nonFraction name="jppfs_cor:NetSales" contextRef="CurrentYearDuration" unitRef="JPY" decimals="-6" scale="6" format="ixt:numdotdecimal">1,234</ix:nonFraction>"#;
+        let xbrl = parse_inline_xbrl(&[
+            page(&format!("{}{}", RESOURCES, net_sales)),
+            page(&format!(
+                "{}{}",
+                net_sales,
+                r#"<ix:nonFraction name="jpcrp_cor:TotalNumberOfIssuedSharesSummaryOfBusinessResults" contextRef="CurrentYearInstant_NonConsolidatedMember" unitRef="shares" decimals="INF" format="ixt:numdotdecimal">29,631,000</ix:nonFraction>"#,
+            )),
+        ]).unwrap();
+
+        let facts = extract_numeric_facts(&xbrl);
+        assert_eq!(facts.len(), 2);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE facts (
+                doc_id TEXT NOT NULL,
+                concept TEXT NOT NULL,
+                context_ref TEXT NOT NULL,
+                period_start TEXT,
+                period_end TEXT,
+                period_instant TEXT,
+                dimensions TEXT,
+                unit_ref TEXT NOT NULL,
+                unit TEXT,
+                decimals TEXT,
+                value REAL,
+                PRIMARY KEY(doc_id, concept, context_ref)
+            );",
+        ).unwrap();
+        // Storing a filing again replaces its facts rather than adding to them.
+        replace_facts(&conn, "S100TEST", &facts).unwrap();
+        replace_facts(&conn, "S100TEST", &facts).unwrap();
+
+        // concept, period start/end/instant, dimensions, unit, decimals, value
+        type Row = (String, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<String>, Option<f64>);
+        let rows: Vec<Row> = conn
+            .prepare(
+                "SELECT concept, period_start, period_end, period_instant, dimensions, unit, decimals, value
+                FROM facts ORDER BY concept",
+            ).unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?, r.get(6)?, r.get(7)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(rows, vec![
+            (
+                "jpcrp_cor:TotalNumberOfIssuedSharesSummaryOfBusinessResults".to_string(),
+                None,
+                None,
+                Some("2024-03-31".to_string()),
+                Some(r#"{"jppfs_cor:ConsolidatedOrNonConsolidatedAxis":"jppfs_cor:NonConsolidatedMember"}"#.to_string()),
+                Some("xbrli:shares".to_string()),
+                Some("INF".to_string()),
+                Some(29631000.0),
+            ),
+            (
+                "jppfs_cor:NetSales".to_string(),
+                Some("2023-04-01".to_string()),
+                Some("2024-03-31".to_string()),
+                None,
+                None,
+                Some("iso4217:JPY".to_string()),
+                Some("-6".to_string()),
+                Some(1234000000.0),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn keeps_share_counts_out_of_scopes_without_statements() {
+        let xbrl = parse_inline_xbrl(&[page(&format!(
+            "{}{}{}",
+            RESOURCES,
+            r#"<ix:nonFraction name="jpcrp_cor:BasicEarningsLossPerShareSummaryOfBusinessResults" contextRef="CurrentYearDuration_NonConsolidatedMember" unitRef="JPYPerShares" decimals="2">37.23</ix:nonFraction>"#,
+            r#"<ix:nonFraction name="jpcrp_cor:TotalNumberOfIssuedSharesSummaryOfBusinessResults" contextRef="CurrentYearInstant_NonConsolidatedMember" unitRef="shares" decimals="INF" format="ixt:numdotdecimal">29,631,000</ix:nonFraction>"#,
+        ))]).unwrap();
+        let scopes: Vec<Scope> = [true, false].map(|c| (&xbrl, AccountingStandard::JapanGaap, Industry::General, c)).to_vec();
+        let year = Period::Duration {
+            start: NaiveDate::from_ymd_opt(2023, 4, 1).unwrap(),
+            end: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        };
+        let non_consolidated = PerShareData {
+            basic_eps: Some(37.23),
+            issued_shares: Some(29631000.0),
+            currency: Some("JPY".to_string()),
+            ..Default::default()
+        };
+
+        // A non-consolidated filer: no consolidated row from share counts alone.
+        let dei = DocumentEntityInfo { consolidated: Some(false), ..Default::default() };
+        let rows = extract_per_share_rows(&scopes, &[year], &HashSet::from([false]), &dei);
+        assert_eq!(rows, vec![(false, year, non_consolidated)]);
+
+        // With consolidated statements the counts fill the consolidated row too.
+        let dei = DocumentEntityInfo { consolidated: Some(true), ..Default::default() };
+        let rows = extract_per_share_rows(&scopes, &[year], &HashSet::from([true, false]), &dei);
+        assert_eq!(rows[0], (true, year, PerShareData { issued_shares: Some(29631000.0), ..Default::default() }));
+        assert_eq!(rows.len(), 2);
+    }
+}
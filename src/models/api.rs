@@ -38,4 +38,7 @@ pub struct DocumentInfo {
     pub submit_date_time: Option<String>,
     #[serde(rename = "filerName")]
     pub filer_name: Option<String>,
+    /// The document an amendment (訂正報告書) corrects.
+    #[serde(rename = "parentDocID")]
+    pub parent_doc_id: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,65 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Which period a field is read for, relative to the statement's period.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldPeriod {
+    /// The statement's own period: the duration of a flow statement or the
+    /// date of a balance sheet.
+    #[default]
+    Statement,
+    /// The balance the day before a flow statement's period starts.
+    Opening,
+    /// The balance on the day a flow statement's period ends.
+    Closing,
+}
+
+/// The concepts a normalized field is read from, in order of preference:
+/// the first one the filing reports wins. Written either as a plain list or
+/// with a period.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum FieldMapping {
+    Concepts(Vec<String>),
+    Detailed {
+        concepts: Vec<String>,
+        #[serde(default)]
+        period: FieldPeriod,
+    },
+}
+
+/// Field path (`net_sales`, `assets.total_assets`) to the concepts it is
+/// read from.
+pub type StatementMapping = BTreeMap<String, FieldMapping>;
+
+/// The concept-to-field mappings of every normalized statement, including
+/// the income statement and balance sheet templates of financial industries.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ConceptMappings {
+    pub income_statement: StatementMapping,
+    pub balance_sheet: StatementMapping,
+    pub cash_flow_statement: StatementMapping,
+    pub bank_income_statement: StatementMapping,
+    pub bank_balance_sheet: StatementMapping,
+    pub insurance_income_statement: StatementMapping,
+    pub insurance_balance_sheet: StatementMapping,
+    pub securities_income_statement: StatementMapping,
+    pub securities_balance_sheet: StatementMapping,
+}
+
+impl FieldMapping {
+    pub fn concepts(&self) -> &[String] {
+        match self {
+            FieldMapping::Concepts(concepts) => concepts,
+            FieldMapping::Detailed { concepts, .. } => concepts,
+        }
+    }
+
+    pub fn period(&self) -> FieldPeriod {
+        match self {
+            FieldMapping::Concepts(_) => FieldPeriod::Statement,
+            FieldMapping::Detailed { period, .. } => *period,
+        }
+    }
+}
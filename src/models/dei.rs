@@ -0,0 +1,27 @@
+use serde::{Serialize, Deserialize};
+use chrono::NaiveDate;
+
+use super::financial_statements::AccountingStandard;
+
+/// The document and entity information (`jpdei_cor:`) every EDINET instance
+/// carries: who filed it and which fiscal period its figures describe.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct DocumentEntityInfo {
+    pub edinet_code: Option<String>,
+    pub security_code: Option<String>,
+    pub filer_name_en: Option<String>,
+    pub document_type: Option<String>,
+    pub accounting_standard: Option<AccountingStandard>,
+    /// Whether consolidated statements are prepared (and so are the primary
+    /// ones).
+    pub consolidated: Option<bool>,
+    pub fiscal_year_start: Option<NaiveDate>,
+    pub fiscal_year_end: Option<NaiveDate>,
+    pub current_period_end: Option<NaiveDate>,
+    /// `Q1`, `Q2`, `Q3`, `HY` or `FY`.
+    pub type_of_current_period: Option<String>,
+    /// The industry code (`cte`, `bk1`, `sec`, ...) of the consolidated and
+    /// of the non-consolidated statements.
+    pub consolidated_industry_code: Option<String>,
+    pub industry_code: Option<String>,
+}
@@ -0,0 +1,78 @@
+/// An EDINET disclosure form the pipeline ingests: the document type codes
+/// it is filed under (original and amendment), the taxonomy its instance is
+/// built on, and the contexts holding its reporting period.
+#[derive(Debug)]
+pub struct DocumentForm {
+    /// The form's entry point, as it appears at the start of the instance
+    /// file name (`jpcrp030000-asr-001_E00000-000_...xbrl`).
+    pub taxonomy: &'static str,
+    pub doc_type_codes: [&'static str; 2],
+    pub duration_context: &'static str,
+    pub instant_context: &'static str,
+}
+
+pub const DOCUMENT_FORMS: &[DocumentForm] = &[
+    // Annual securities report.
+    DocumentForm {
+        taxonomy: "jpcrp030000-asr",
+        doc_type_codes: ["120", "130"],
+        duration_context: "CurrentYearDuration",
+        instant_context: "CurrentYearInstant",
+    },
+    // Quarterly securities report (Q1).
+    DocumentForm {
+        taxonomy: "jpcrp040300-q1r",
+        doc_type_codes: ["140", "150"],
+        duration_context: "CurrentYTDDuration",
+        instant_context: "CurrentQuarterInstant",
+    },
+    // Quarterly securities report (Q2).
+    DocumentForm {
+        taxonomy: "jpcrp040300-q2r",
+        doc_type_codes: ["140", "150"],
+        duration_context: "CurrentYTDDuration",
+        instant_context: "CurrentQuarterInstant",
+    },
+    // Quarterly securities report (Q3).
+    DocumentForm {
+        taxonomy: "jpcrp040300-q3r",
+        doc_type_codes: ["140", "150"],
+        duration_context: "CurrentYTDDuration",
+        instant_context: "CurrentQuarterInstant",
+    },
+    // Semi-annual securities report (Form 5), filed by companies that never
+    // reported quarterly.
+    DocumentForm {
+        taxonomy: "jpcrp050000-ssr",
+        doc_type_codes: ["160", "170"],
+        duration_context: "InterimDuration",
+        instant_context: "InterimInstant",
+    },
+    // Half-year securities report (Form 4-3), which replaced the Q2 quarterly
+    // report for listed companies from April 2024.
+    DocumentForm {
+        taxonomy: "jpcrp040300-ssr",
+        doc_type_codes: ["160", "170"],
+        duration_context: "InterimDuration",
+        instant_context: "InterimInstant",
+    },
+];
+
+/// Whether documents of an EDINET document type code are ingested.
+pub fn is_supported_doc_type(code: &str) -> bool {
+    DOCUMENT_FORMS.iter().any(|f| f.doc_type_codes.contains(&code))
+}
+
+/// The form of a filing, told by the taxonomy entry point its instance or
+/// inline XBRL file names start with.
+pub fn form_of_file(path: &str) -> Option<&'static DocumentForm> {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    // Inline XBRL pages carry a section prefix (`0101010_honbun_`).
+    let file_name = file_name.rsplit_once("_honbun_")
+        .or_else(|| file_name.rsplit_once("_header_"))
+        .map_or(file_name, |(_, rest)| rest);
+
+    DOCUMENT_FORMS.iter().find(|f| {
+        file_name.strip_prefix(f.taxonomy).is_some_and(|rest| rest.starts_with('-'))
+    })
+}
@@ -1,6 +1,8 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+use super::xbrl::Period;
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct IncomeStatement {
     pub net_sales: Option<f64>,
     pub cost_of_sales: Option<f64>,
@@ -30,35 +32,491 @@ pub struct IncomeStatement {
     pub net_income: Option<f64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// Balance sheet following the standard `jppfs_cor` hierarchy: current and
+/// non-current subtotals with their main line items, including borrowings
+/// and the components of net assets.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
 pub struct BalanceSheet {
     pub assets: Assets,
     pub liabilities: Liabilities,
     pub equity: Equity,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct Assets {
+    pub current_assets: Option<f64>,
     pub cash_and_deposits: Option<f64>,
     pub notes_and_accounts_receivable_trade: Option<f64>,
+    pub notes_receivable_trade: Option<f64>,
+    pub accounts_receivable_trade: Option<f64>,
+    pub electronically_recorded_monetary_claims_operating: Option<f64>,
     pub short_term_investment_securities: Option<f64>,
     pub merchandise: Option<f64>,
+    pub finished_goods: Option<f64>,
+    pub merchandise_and_finished_goods: Option<f64>,
+    pub work_in_process: Option<f64>,
+    pub raw_materials_and_supplies: Option<f64>,
+    pub inventories: Option<f64>,
+    pub short_term_loans_receivable: Option<f64>,
+    pub deferred_tax_assets_current: Option<f64>,
+    pub other_current_assets: Option<f64>,
+    pub allowance_for_doubtful_accounts_current: Option<f64>,
+    pub noncurrent_assets: Option<f64>,
     pub property_plant_and_equipment: Option<f64>,
+    pub buildings_and_structures_net: Option<f64>,
+    pub machinery_equipment_and_vehicles_net: Option<f64>,
+    pub land: Option<f64>,
+    pub lease_assets_net: Option<f64>,
+    pub construction_in_progress: Option<f64>,
     pub intangible_assets: Option<f64>,
+    pub goodwill: Option<f64>,
+    pub software: Option<f64>,
     pub investments_and_other_assets: Option<f64>,
+    pub investment_securities: Option<f64>,
+    pub long_term_loans_receivable: Option<f64>,
+    pub lease_and_guarantee_deposits: Option<f64>,
+    pub net_defined_benefit_asset: Option<f64>,
+    pub deferred_tax_assets: Option<f64>,
+    pub allowance_for_doubtful_accounts_noncurrent: Option<f64>,
+    pub deferred_assets: Option<f64>,
     pub total_assets: Option<f64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct Liabilities {
     pub current_liabilities: Option<f64>,
+    pub notes_and_accounts_payable_trade: Option<f64>,
+    pub accounts_payable_trade: Option<f64>,
+    pub electronically_recorded_obligations_operating: Option<f64>,
+    pub short_term_loans_payable: Option<f64>,
+    pub commercial_papers: Option<f64>,
+    pub current_portion_of_bonds: Option<f64>,
+    pub current_portion_of_long_term_loans_payable: Option<f64>,
+    pub lease_obligations_current: Option<f64>,
+    pub accounts_payable_other: Option<f64>,
+    pub income_taxes_payable: Option<f64>,
+    pub provision_for_bonuses: Option<f64>,
+    pub deferred_tax_liabilities_current: Option<f64>,
+    pub other_current_liabilities: Option<f64>,
     pub noncurrent_liabilities: Option<f64>,
+    pub bonds_payable: Option<f64>,
+    pub long_term_loans_payable: Option<f64>,
+    pub lease_obligations_noncurrent: Option<f64>,
+    pub deferred_tax_liabilities: Option<f64>,
+    pub provision_for_retirement_benefits: Option<f64>,
+    pub net_defined_benefit_liability: Option<f64>,
+    pub asset_retirement_obligations: Option<f64>,
+    pub other_noncurrent_liabilities: Option<f64>,
     pub total_liabilities: Option<f64>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
 pub struct Equity {
+    pub capital_stock: Option<f64>,
+    pub capital_surplus: Option<f64>,
+    pub retained_earnings: Option<f64>,
+    pub treasury_stock: Option<f64>,
     pub shareholders_equity: Option<f64>,
+    pub valuation_difference_on_available_for_sale_securities: Option<f64>,
+    pub deferred_gains_or_losses_on_hedges: Option<f64>,
+    pub foreign_currency_translation_adjustment: Option<f64>,
+    pub remeasurements_of_defined_benefit_plans: Option<f64>,
     pub valuation_and_translation_adjustments: Option<f64>,
+    pub subscription_rights_to_shares: Option<f64>,
+    pub non_controlling_interests: Option<f64>,
     pub total_equity: Option<f64>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct CashFlowStatement {
+    pub income_before_income_taxes: Option<f64>,
+    pub depreciation_and_amortization: Option<f64>,
+    pub impairment_loss: Option<f64>,
+    pub amortization_of_goodwill: Option<f64>,
+    pub decrease_increase_in_notes_and_accounts_receivable_trade: Option<f64>,
+    pub decrease_increase_in_inventories: Option<f64>,
+    pub increase_decrease_in_notes_and_accounts_payable_trade: Option<f64>,
+    pub subtotal_operating: Option<f64>,
+    pub income_taxes_paid: Option<f64>,
+    pub net_cash_operating: Option<f64>,
+    pub purchase_of_property_plant_and_equipment: Option<f64>,
+    pub proceeds_from_sales_of_property_plant_and_equipment: Option<f64>,
+    pub purchase_of_intangible_assets: Option<f64>,
+    pub purchase_of_investment_securities: Option<f64>,
+    pub proceeds_from_sales_of_investment_securities: Option<f64>,
+    pub net_cash_investing: Option<f64>,
+    pub net_increase_decrease_in_short_term_loans_payable: Option<f64>,
+    pub proceeds_from_long_term_loans_payable: Option<f64>,
+    pub repayment_of_long_term_loans_payable: Option<f64>,
+    pub proceeds_from_issuance_of_bonds: Option<f64>,
+    pub redemption_of_bonds: Option<f64>,
+    pub purchase_of_treasury_stock: Option<f64>,
+    pub cash_dividends_paid: Option<f64>,
+    pub net_cash_financing: Option<f64>,
+    pub effect_of_exchange_rate_change: Option<f64>,
+    pub net_increase_decrease_in_cash: Option<f64>,
+    pub cash_and_cash_equivalents_beginning: Option<f64>,
+    pub cash_and_cash_equivalents_end: Option<f64>,
+}
+
+/// Income statement of a bank (`BNK` concepts): ordinary income and
+/// expenses by source instead of sales and cost of sales.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct BankIncomeStatement {
+    pub ordinary_income: Option<f64>,
+    pub interest_income: Option<f64>,
+    pub interest_on_loans_and_discounts: Option<f64>,
+    pub interest_and_dividends_on_securities: Option<f64>,
+    pub trust_fees: Option<f64>,
+    pub fees_and_commissions_income: Option<f64>,
+    pub trading_income: Option<f64>,
+    pub other_operating_income: Option<f64>,
+    pub other_ordinary_income: Option<f64>,
+    pub ordinary_expenses: Option<f64>,
+    pub interest_expenses: Option<f64>,
+    pub interest_on_deposits: Option<f64>,
+    pub fees_and_commissions_expenses: Option<f64>,
+    pub other_operating_expenses: Option<f64>,
+    pub general_and_administrative_expenses: Option<f64>,
+    pub other_ordinary_expenses: Option<f64>,
+    pub ordinary_profit: Option<f64>,
+    pub extraordinary_income: Option<f64>,
+    pub extraordinary_loss: Option<f64>,
+    pub income_before_income_taxes: Option<f64>,
+    pub income_taxes: Option<f64>,
+    pub net_income: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct BankBalanceSheet {
+    pub assets: BankAssets,
+    pub liabilities: BankLiabilities,
+    pub equity: Equity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct BankAssets {
+    pub cash_and_due_from_banks: Option<f64>,
+    pub call_loans_and_bills_bought: Option<f64>,
+    pub monetary_claims_bought: Option<f64>,
+    pub trading_assets: Option<f64>,
+    pub money_held_in_trust: Option<f64>,
+    pub securities: Option<f64>,
+    pub loans_and_bills_discounted: Option<f64>,
+    pub foreign_exchanges: Option<f64>,
+    pub customers_liabilities_for_acceptances_and_guarantees: Option<f64>,
+    pub allowance_for_loan_losses: Option<f64>,
+    pub total_assets: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct BankLiabilities {
+    pub deposits: Option<f64>,
+    pub negotiable_certificates_of_deposit: Option<f64>,
+    pub call_money_and_bills_sold: Option<f64>,
+    pub payables_under_repurchase_agreements: Option<f64>,
+    pub trading_liabilities: Option<f64>,
+    pub borrowed_money: Option<f64>,
+    pub foreign_exchanges_liabilities: Option<f64>,
+    pub bonds_payable: Option<f64>,
+    pub acceptances_and_guarantees: Option<f64>,
+    pub total_liabilities: Option<f64>,
+}
+
+/// Income statement of a life or non-life insurer (`INS` concepts):
+/// premiums, investment income and claims.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct InsuranceIncomeStatement {
+    pub ordinary_income: Option<f64>,
+    pub insurance_premiums: Option<f64>,
+    pub investment_income: Option<f64>,
+    pub other_ordinary_income: Option<f64>,
+    pub ordinary_expenses: Option<f64>,
+    pub insurance_claims: Option<f64>,
+    pub provision_for_policy_reserves: Option<f64>,
+    pub investment_expenses: Option<f64>,
+    pub operating_expenses: Option<f64>,
+    pub other_ordinary_expenses: Option<f64>,
+    pub ordinary_profit: Option<f64>,
+    pub extraordinary_income: Option<f64>,
+    pub extraordinary_loss: Option<f64>,
+    pub income_before_income_taxes: Option<f64>,
+    pub income_taxes: Option<f64>,
+    pub net_income: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct InsuranceBalanceSheet {
+    pub assets: InsuranceAssets,
+    pub liabilities: InsuranceLiabilities,
+    pub equity: Equity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct InsuranceAssets {
+    pub cash_and_deposits: Option<f64>,
+    pub call_loans: Option<f64>,
+    pub monetary_claims_bought: Option<f64>,
+    pub money_held_in_trust: Option<f64>,
+    pub securities: Option<f64>,
+    pub loans: Option<f64>,
+    pub tangible_fixed_assets: Option<f64>,
+    pub intangible_fixed_assets: Option<f64>,
+    pub total_assets: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct InsuranceLiabilities {
+    pub policy_reserves: Option<f64>,
+    pub reserve_for_outstanding_claims: Option<f64>,
+    pub underwriting_reserves: Option<f64>,
+    pub bonds_payable: Option<f64>,
+    pub total_liabilities: Option<f64>,
+}
+
+/// Income statement of a securities firm (`SEC` concepts): operating
+/// revenue from commissions, trading and financing, less financial expenses.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SecuritiesIncomeStatement {
+    pub operating_revenue: Option<f64>,
+    pub commissions_received: Option<f64>,
+    pub net_trading_income: Option<f64>,
+    pub financial_revenue: Option<f64>,
+    pub financial_expenses: Option<f64>,
+    pub net_operating_revenue: Option<f64>,
+    pub selling_general_admin: Option<f64>,
+    pub operating_income: Option<f64>,
+    pub ordinary_income: Option<f64>,
+    pub extraordinary_income: Option<f64>,
+    pub extraordinary_loss: Option<f64>,
+    pub income_before_income_taxes: Option<f64>,
+    pub income_taxes: Option<f64>,
+    pub net_income: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct SecuritiesBalanceSheet {
+    pub assets: SecuritiesAssets,
+    pub liabilities: SecuritiesLiabilities,
+    pub equity: Equity,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SecuritiesAssets {
+    pub cash_and_deposits: Option<f64>,
+    pub cash_segregated_as_deposits: Option<f64>,
+    pub trading_products: Option<f64>,
+    pub margin_transaction_assets: Option<f64>,
+    pub loans_secured_by_securities: Option<f64>,
+    pub current_assets: Option<f64>,
+    pub total_assets: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct SecuritiesLiabilities {
+    pub trading_products_liabilities: Option<f64>,
+    pub margin_transaction_liabilities: Option<f64>,
+    pub loans_payable_secured_by_securities: Option<f64>,
+    pub deposits_received: Option<f64>,
+    pub guarantee_deposits_received: Option<f64>,
+    pub short_term_loans_payable: Option<f64>,
+    pub current_liabilities: Option<f64>,
+    pub total_liabilities: Option<f64>,
+}
+
+/// Per-share amounts for a period and the share counts at its end, from the
+/// summary of business results and the share capital section.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct PerShareData {
+    pub basic_eps: Option<f64>,
+    pub diluted_eps: Option<f64>,
+    pub dividends_per_share: Option<f64>,
+    pub book_value_per_share: Option<f64>,
+    pub issued_shares: Option<f64>,
+    pub treasury_shares: Option<f64>,
+    /// The currency of the per-share amounts (`JPY` for `JPYPerShares`).
+    pub currency: Option<String>,
+}
+
+/// The accounting standard a filing's financial statements follow, as
+/// reported in `jpdei_cor:AccountingStandardsDEI`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccountingStandard {
+    #[default]
+    JapanGaap,
+    Ifrs,
+    UsGaap,
+}
+
+/// The statement layout a filer's Japanese GAAP statements follow, from the
+/// industry codes in the DEI (`cte`, `bk1`, `in2`, ...). Industries without a
+/// template of their own (construction, railways, ...) use the general
+/// commercial and industrial one.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Industry {
+    #[default]
+    General,
+    Bank,
+    Insurance,
+    Securities,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquityLineKind {
+    OpeningBalance,
+    Movement,
+    ClosingBalance,
+}
+
+/// One cell of the statement of changes in equity: a movement line (or
+/// opening/closing balance) of one equity component column.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EquityChange {
+    pub component: String,
+    pub kind: EquityLineKind,
+    pub concept: String,
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChangesInEquityStatement {
+    pub changes: Vec<EquityChange>,
+}
+
+/// A numeric fact of one operating segment (or of a segment total such as
+/// reconciling items) for one period.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SegmentValue {
+    pub segment: String,
+    pub concept: String,
+    pub period: Period,
+    pub unit_ref: Option<String>,
+    pub value: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    BalanceSheet,
+    IncomeStatement,
+    ComprehensiveIncome,
+    CashFlows,
+    ChangesInEquity,
+}
+
+/// A statement rebuilt from the filer's presentation linkbase, with line
+/// items in filed order.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PresentedStatement {
+    pub role: String,
+    pub kind: StatementKind,
+    pub consolidated: bool,
+    pub line_items: Vec<StatementLineItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StatementLineItem {
+    pub concept: String,
+    pub depth: usize,
+    pub preferred_label: Option<String>,
+    pub label: Option<String>,
+    pub value: Option<f64>,
+    /// The value in the prior period's column.
+    pub prior_value: Option<f64>,
+}
+
+impl AccountingStandard {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "Japan GAAP" => Some(AccountingStandard::JapanGaap),
+            "IFRS" => Some(AccountingStandard::Ifrs),
+            "US GAAP" => Some(AccountingStandard::UsGaap),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountingStandard::JapanGaap => "Japan GAAP",
+            AccountingStandard::Ifrs => "IFRS",
+            AccountingStandard::UsGaap => "US GAAP",
+        }
+    }
+
+    /// The taxonomy the primary statements are tagged with. US GAAP filers
+    /// only tag the summary of business results (`jpcrp_cor:...USGAAP...`).
+    pub fn statement_prefix(&self) -> &'static str {
+        match self {
+            AccountingStandard::JapanGaap => "jppfs_cor:",
+            AccountingStandard::Ifrs => "jpigp_cor:",
+            AccountingStandard::UsGaap => "jpcrp_cor:",
+        }
+    }
+
+    /// The standard of the consolidated or the non-consolidated statements.
+    /// IFRS and US GAAP apply to consolidated statements only; standalone
+    /// statements are always prepared under Japanese GAAP.
+    pub fn for_statements(&self, consolidated: bool) -> Self {
+        if consolidated { *self } else { AccountingStandard::JapanGaap }
+    }
+}
+
+impl PerShareData {
+    /// Whether any per-share amount was reported, as opposed to share counts
+    /// only.
+    pub fn has_amounts(&self) -> bool {
+        self.basic_eps.is_some()
+            || self.diluted_eps.is_some()
+            || self.dividends_per_share.is_some()
+            || self.book_value_per_share.is_some()
+    }
+}
+
+impl Industry {
+    pub const FINANCIAL: [Industry; 3] = [Industry::Bank, Industry::Insurance, Industry::Securities];
+
+    pub fn parse(code: &str) -> Self {
+        match code.trim().to_ascii_lowercase().as_str() {
+            "bk1" | "bk2" => Industry::Bank,
+            "in1" | "in2" => Industry::Insurance,
+            "sec" => Industry::Securities,
+            _ => Industry::General,
+        }
+    }
+
+    /// The suffix of the industry's own `jppfs_cor` concepts, e.g.
+    /// `DepositsLiabilitiesBNK`.
+    pub fn concept_suffix(&self) -> Option<&'static str> {
+        match self {
+            Industry::General => None,
+            Industry::Bank => Some("BNK"),
+            Industry::Insurance => Some("INS"),
+            Industry::Securities => Some("SEC"),
+        }
+    }
+}
+
+impl EquityLineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EquityLineKind::OpeningBalance => "opening_balance",
+            EquityLineKind::Movement => "movement",
+            EquityLineKind::ClosingBalance => "closing_balance",
+        }
+    }
+}
+
+impl StatementKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementKind::BalanceSheet => "balance_sheet",
+            StatementKind::IncomeStatement => "income_statement",
+            StatementKind::ComprehensiveIncome => "comprehensive_income",
+            StatementKind::CashFlows => "cash_flows",
+            StatementKind::ChangesInEquity => "changes_in_equity",
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+pub const STANDARD_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/label";
+pub const TERSE_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/terseLabel";
+pub const VERBOSE_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/verboseLabel";
+
+pub const ALL_ARCROLE: &str = "http://xbrl.org/int/dim/arcrole/all";
+pub const HYPERCUBE_DIMENSION_ARCROLE: &str = "http://xbrl.org/int/dim/arcrole/hypercube-dimension";
+pub const DIMENSION_DOMAIN_ARCROLE: &str = "http://xbrl.org/int/dim/arcrole/dimension-domain";
+pub const DIMENSION_DEFAULT_ARCROLE: &str = "http://xbrl.org/int/dim/arcrole/dimension-default";
+pub const DOMAIN_MEMBER_ARCROLE: &str = "http://xbrl.org/int/dim/arcrole/domain-member";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Label {
+    pub concept: String,
+    pub lang: String,
+    pub role: String,
+    pub text: String,
+}
+
+/// Labels of every concept found in the loaded label linkbases, keyed by
+/// concept QName (e.g. `jppfs_cor:NetSales`).
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct LabelLinkbase {
+    pub labels: HashMap<String, Vec<Label>>,
+}
+
+impl LabelLinkbase {
+    pub fn insert(&mut self, label: Label) {
+        let labels = self.labels.entry(label.concept.clone()).or_default();
+        if !labels.contains(&label) {
+            labels.push(label);
+        }
+    }
+
+    /// Adds a label in place of the concept's label of the same language and
+    /// role, e.g. a filer's own label for a standard concept.
+    pub fn replace(&mut self, label: Label) {
+        let labels = self.labels.entry(label.concept.clone()).or_default();
+        labels.retain(|l| l.lang != label.lang || l.role != label.role);
+        labels.push(label);
+    }
+
+    pub fn label(&self, concept: &str, lang: &str, role: &str) -> Option<&str> {
+        self.labels.get(concept)?
+            .iter()
+            .find(|l| l.lang == lang && l.role == role)
+            .map(|l| l.text.as_str())
+    }
+
+    /// The label for an arc's preferred label role, falling back to the
+    /// standard label.
+    pub fn preferred_label(&self, concept: &str, lang: &str, role: Option<&str>) -> Option<&str> {
+        role.and_then(|r| self.label(concept, lang, r))
+            .or_else(|| self.label(concept, lang, STANDARD_LABEL_ROLE))
+    }
+}
+
+/// A single arc of a presentation, calculation or definition linkbase,
+/// resolved from locator labels to concept QNames.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Relationship {
+    pub arcrole: String,
+    pub from: String,
+    pub to: String,
+    pub order: f64,
+    pub weight: Option<f64>,
+    pub preferred_label: Option<String>,
+}
+
+/// All relationships of one extended link role (e.g. the quarterly
+/// consolidated balance sheet).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Network {
+    pub role: String,
+    pub relationships: Vec<Relationship>,
+}
+
+/// A hypercube (`...Table`) of the definition linkbase: the primary items it
+/// applies to and the dimensions (`...Axis`) that break them down.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hypercube {
+    pub role: String,
+    pub table: String,
+    pub primary_items: Vec<String>,
+    pub dimensions: Vec<Dimension>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Dimension {
+    pub axis: String,
+    pub domain: Option<String>,
+    pub default: Option<String>,
+    pub members: Vec<DomainMember>,
+}
+
+/// A member of a dimension's domain, in linkbase order, with the member it
+/// rolls up into (`None` for the domain itself).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DomainMember {
+    pub member: String,
+    pub parent: Option<String>,
+}
+
+impl Network {
+    pub fn children(&self, concept: &str) -> Vec<&Relationship> {
+        let mut children: Vec<&Relationship> = self.relationships.iter()
+            .filter(|r| r.from == concept)
+            .collect();
+        children.sort_by(|a, b| a.order.total_cmp(&b.order));
+        children
+    }
+
+    /// Concepts reachable from `concept` through `arcrole` arcs, depth first,
+    /// with the concept each one was reached from.
+    fn descendants<'a>(&'a self, concept: &'a str, arcrole: &str, out: &mut Vec<(&'a str, &'a str)>) {
+        for child in self.children(concept).into_iter().filter(|r| r.arcrole == arcrole) {
+            if child.to != concept && !out.iter().any(|(c, _)| *c == child.to) {
+                out.push((&child.to, concept));
+                self.descendants(&child.to, arcrole, out);
+            }
+        }
+    }
+
+    /// The hypercubes declared in a definition linkbase network.
+    pub fn hypercubes(&self) -> Vec<Hypercube> {
+        let mut hypercubes: Vec<Hypercube> = Vec::new();
+
+        for all in self.relationships.iter().filter(|r| r.arcrole == ALL_ARCROLE) {
+            let mut primary = Vec::new();
+            self.descendants(&all.from, DOMAIN_MEMBER_ARCROLE, &mut primary);
+
+            let hypercube = match hypercubes.iter_mut().position(|h| h.table == all.to) {
+                Some(i) => &mut hypercubes[i],
+                None => {
+                    let dimensions = self.children(&all.to)
+                        .into_iter()
+                        .filter(|r| r.arcrole == HYPERCUBE_DIMENSION_ARCROLE)
+                        .map(|r| self.dimension(&r.to))
+                        .collect();
+                    hypercubes.push(Hypercube {
+                        role: self.role.clone(),
+                        table: all.to.clone(),
+                        primary_items: Vec::new(),
+                        dimensions,
+                    });
+                    hypercubes.last_mut().expect("just pushed")
+                }
+            };
+
+            for item in std::iter::once(all.from.as_str()).chain(primary.into_iter().map(|(c, _)| c)) {
+                if !hypercube.primary_items.iter().any(|p| p == item) {
+                    hypercube.primary_items.push(item.to_string());
+                }
+            }
+        }
+
+        hypercubes
+    }
+
+    fn dimension(&self, axis: &str) -> Dimension {
+        let targets = |arcrole: &str| {
+            self.children(axis)
+                .into_iter()
+                .find(|r| r.arcrole == arcrole)
+                .map(|r| r.to.clone())
+        };
+        let domain = targets(DIMENSION_DOMAIN_ARCROLE);
+
+        let mut members = Vec::new();
+        if let Some(domain) = &domain {
+            members.push(DomainMember { member: domain.clone(), parent: None });
+
+            let mut descendants = Vec::new();
+            self.descendants(domain, DOMAIN_MEMBER_ARCROLE, &mut descendants);
+            members.extend(descendants.into_iter().map(|(member, parent)| DomainMember {
+                member: member.to_string(),
+                parent: Some(parent.to_string()),
+            }));
+        }
+
+        Dimension {
+            axis: axis.to_string(),
+            domain,
+            default: targets(DIMENSION_DEFAULT_ARCROLE),
+            members,
+        }
+    }
+
+    /// Concepts that start a tree: sources of arcs that are never targets.
+    pub fn roots(&self) -> Vec<&str> {
+        let mut roots: Vec<&str> = Vec::new();
+        for r in &self.relationships {
+            if !roots.contains(&r.from.as_str()) && !self.relationships.iter().any(|other| other.to == r.from) {
+                roots.push(&r.from);
+            }
+        }
+        roots
+    }
+}
+
+/// A summation in the calculation linkbase that does not hold for the
+/// reported facts of one context, even allowing for the rounding implied by
+/// their `decimals`. `decimals` is the lowest precision among those facts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CalculationInconsistency {
+    pub role: String,
+    pub concept: String,
+    pub context_ref: String,
+    pub reported: f64,
+    pub computed: f64,
+    pub decimals: Option<i32>,
+}
@@ -2,4 +2,9 @@
 
 pub mod api;
 pub mod xbrl;
-pub mod financial_statements;
\ No newline at end of file
+pub mod financial_statements;
+pub mod linkbase;
+pub mod taxonomy;
+pub mod dei;
+pub mod document_type;
+pub mod concept_mapping;
\ No newline at end of file
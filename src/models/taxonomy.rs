@@ -0,0 +1,82 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodType {
+    Instant,
+    Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Balance {
+    Debit,
+    Credit,
+}
+
+/// A concept declared in a taxonomy schema (`xsd:element` with an
+/// `xbrli:periodType`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ConceptDefinition {
+    pub concept: String,
+    pub period_type: Option<PeriodType>,
+    pub balance: Option<Balance>,
+    pub data_type: Option<String>,
+    pub substitution_group: Option<String>,
+    pub is_abstract: bool,
+    pub nillable: bool,
+}
+
+/// Concept declarations of a filer's extension schema and the standard
+/// taxonomy schemas it imports, keyed by concept QName.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Taxonomy {
+    pub concepts: HashMap<String, ConceptDefinition>,
+    /// Cached label linkbases (Japanese and English) of the standard
+    /// schemas, which label every `jppfs_cor`, `jpcrp_cor`, ... concept.
+    pub label_linkbases: Vec<PathBuf>,
+}
+
+impl PeriodType {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "instant" => Some(PeriodType::Instant),
+            "duration" => Some(PeriodType::Duration),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PeriodType::Instant => "instant",
+            PeriodType::Duration => "duration",
+        }
+    }
+}
+
+impl Balance {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "debit" => Some(Balance::Debit),
+            "credit" => Some(Balance::Credit),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Balance::Debit => "debit",
+            Balance::Credit => "credit",
+        }
+    }
+}
+
+impl Taxonomy {
+    pub fn concept(&self, concept: &str) -> Option<&ConceptDefinition> {
+        self.concepts.get(concept)
+    }
+
+    pub fn period_type(&self, concept: &str) -> Option<PeriodType> {
+        self.concept(concept)?.period_type
+    }
+}
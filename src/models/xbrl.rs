@@ -1,6 +1,13 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use serde_json::Value as JsonValue;
+use std::fmt;
+use chrono::NaiveDate;
+
+pub const CONSOLIDATION_AXIS: &str = "jppfs_cor:ConsolidatedOrNonConsolidatedAxis";
+pub const NON_CONSOLIDATED_MEMBER: &str = "jppfs_cor:NonConsolidatedMember";
+pub const OPERATING_SEGMENTS_AXIS: &str = "jpcrp_cor:OperatingSegmentsAxis";
+/// Column axes of the statement of changes in equity (Japanese GAAP, IFRS).
+pub const EQUITY_COMPONENT_AXES: [&str; 2] = ["jppfs_cor:ComponentsOfEquityAxis", "jpigp_cor:ComponentsOfEquityIFRSAxis"];
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct XBRLElement {
@@ -10,11 +17,438 @@ pub struct XBRLElement {
     pub children: Vec<XBRLElement>,
     pub context_ref: Option<String>,
     pub unit_ref: Option<String>,
+    pub unit: Option<XBRLUnit>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct DynamicXBRLContent {
     pub namespaces: HashMap<String, String>,
-    pub contexts: HashMap<String, JsonValue>,
+    pub contexts: HashMap<String, XBRLContext>,
+    pub units: HashMap<String, XBRLUnit>,
     pub elements: Vec<XBRLElement>,
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct XBRLContext {
+    pub id: String,
+    pub entity: EntityIdentifier,
+    pub period: Period,
+    pub segment: Vec<DimensionMember>,
+    pub scenario: Vec<DimensionMember>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct EntityIdentifier {
+    pub scheme: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    Instant(NaiveDate),
+    Duration { start: NaiveDate, end: NaiveDate },
+    Forever,
+}
+
+/// A dimension/member pair from a context's segment or scenario. For typed
+/// dimensions `member` holds the typed value rather than a member QName.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DimensionMember {
+    pub dimension: String,
+    pub member: String,
+    pub typed: bool,
+}
+
+/// An `xbrli:unit` definition. Simple units only have numerator measures;
+/// divide units (e.g. JPY per share) also carry denominator measures.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct XBRLUnit {
+    pub id: String,
+    pub numerator: Vec<String>,
+    pub denominator: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitKind {
+    Monetary,
+    PerShare,
+    Shares,
+    Pure,
+    Other,
+}
+
+/// A narrative (`textBlockItemType`) fact: its HTML as filed and the same
+/// content as plain text.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TextBlock {
+    pub concept: String,
+    pub context_ref: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// The `decimals` attribute of a numeric fact.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decimals {
+    Infinite,
+    Finite(i32),
+}
+
+/// A numeric fact with its context and unit resolved: one row of the
+/// generic facts table. Nil facts have no value.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportedFact {
+    pub concept: String,
+    pub context_ref: String,
+    pub period: Period,
+    pub dimensions: Vec<DimensionMember>,
+    pub unit_ref: String,
+    pub unit: Option<String>,
+    pub decimals: Option<Decimals>,
+    pub value: Option<f64>,
+}
+
+/// A numeric fact value with scale and sign applied. Nil facts keep their
+/// precision and unit but have no value, so callers can tell "reported as
+/// nil" apart from "not reported at all" (no `NumericFact`).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct NumericFact {
+    pub value: Option<f64>,
+    pub decimals: Option<Decimals>,
+    pub nil: bool,
+}
+
+impl Period {
+    pub fn start_date(&self) -> Option<NaiveDate> {
+        match self {
+            Period::Duration { start, .. } => Some(*start),
+            _ => None,
+        }
+    }
+
+    pub fn end_date(&self) -> Option<NaiveDate> {
+        match self {
+            Period::Instant(date) => Some(*date),
+            Period::Duration { end, .. } => Some(*end),
+            Period::Forever => None,
+        }
+    }
+}
+
+impl XBRLUnit {
+    /// The unit's measures, e.g. `iso4217:JPY` or `iso4217:JPY/xbrli:shares`.
+    pub fn measure(&self) -> String {
+        let numerator = self.numerator.join("*");
+        if self.denominator.is_empty() {
+            numerator
+        } else {
+            format!("{}/{}", numerator, self.denominator.join("*"))
+        }
+    }
+
+    pub fn kind(&self) -> UnitKind {
+        let is_currency = |m: &String| m.starts_with("iso4217:");
+        let is_shares = |m: &String| m == "xbrli:shares";
+
+        match (self.numerator.as_slice(), self.denominator.as_slice()) {
+            ([n], []) if is_currency(n) => UnitKind::Monetary,
+            ([n], []) if is_shares(n) => UnitKind::Shares,
+            ([n], []) if n == "xbrli:pure" => UnitKind::Pure,
+            ([n], [d]) if is_currency(n) && is_shares(d) => UnitKind::PerShare,
+            _ => UnitKind::Other,
+        }
+    }
+}
+
+impl XBRLContext {
+    pub fn dimensions(&self) -> impl Iterator<Item = &DimensionMember> {
+        self.segment.iter().chain(self.scenario.iter())
+    }
+
+    pub fn has_dimensions(&self) -> bool {
+        self.dimensions().next().is_some()
+    }
+
+    /// Whether the only dimension is the non-consolidated member, which is
+    /// how EDINET tags standalone financial statements.
+    pub fn is_non_consolidated(&self) -> bool {
+        let mut dimensions = self.dimensions();
+        matches!(
+            (dimensions.next(), dimensions.next()),
+            (Some(d), None) if d.dimension == CONSOLIDATION_AXIS && d.member == NON_CONSOLIDATED_MEMBER
+        )
+    }
+
+    /// Whether the context holds facts of the consolidated statements (no
+    /// dimensions) or of the non-consolidated ones (only the
+    /// non-consolidated member).
+    pub fn is_statement_context(&self, consolidated: bool) -> bool {
+        if consolidated {
+            !self.has_dimensions()
+        } else {
+            self.is_non_consolidated()
+        }
+    }
+}
+
+impl Decimals {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "INF" => Some(Decimals::Infinite),
+            d => d.parse().ok().map(Decimals::Finite),
+        }
+    }
+}
+
+impl fmt::Display for Decimals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Decimals::Infinite => write!(f, "INF"),
+            Decimals::Finite(d) => write!(f, "{}", d),
+        }
+    }
+}
+
+impl XBRLElement {
+    pub fn unit_kind(&self) -> Option<UnitKind> {
+        self.unit.as_ref().map(XBRLUnit::kind)
+    }
+
+    pub fn is_nil(&self) -> bool {
+        self.attributes.get("xsi:nil").is_some_and(|v| v == "true" || v == "1")
+    }
+
+    /// Reads the fact as a number, honouring `xsi:nil`, `decimals` and the
+    /// inline XBRL `format`, `scale` and `sign` attributes. Returns `None` for
+    /// non-numeric facts and values that cannot be parsed.
+    pub fn numeric_value(&self) -> Option<NumericFact> {
+        self.unit_ref.as_ref()?;
+
+        let decimals = self.attributes.get("decimals").and_then(|d| Decimals::parse(d));
+
+        if self.is_nil() {
+            return Some(NumericFact { value: None, decimals, nil: true });
+        }
+
+        let format = self.attributes.get("format").map(String::as_str);
+        let digits = normalize_number(self.value.as_deref()?, format)?;
+
+        // Shift the exponent instead of multiplying so that e.g. "17.55" with
+        // scale -2 reads as exactly 0.1755.
+        let scale = self.attributes.get("scale").and_then(|s| s.trim().parse::<i32>().ok()).unwrap_or(0);
+        let mut value = format!("{}e{}", digits, scale).parse::<f64>().ok()?;
+
+        if self.attributes.get("sign").is_some_and(|s| s == "-") {
+            value = -value;
+        }
+
+        Some(NumericFact { value: Some(value), decimals, nil: false })
+    }
+}
+
+/// Normalizes displayed numeric text according to an inline XBRL
+/// transformation (`ixt:numdotdecimal`, `ixt:numcommadecimal`,
+/// `ixt:zerodash`, ...) into a plain decimal string. Plain instance values
+/// have no format and are only trimmed.
+fn normalize_number(text: &str, format: Option<&str>) -> Option<String> {
+    let text = text.trim();
+    let format = format.map(|f| f.rsplit(':').next().unwrap_or(f));
+
+    let cleaned: String = match format {
+        Some("zerodash") | Some("fixed-zero") => "0".to_string(),
+        Some("numcommadecimal") | Some("num-comma-decimal") => text
+            .chars()
+            .filter(|c| !matches!(c, '.' | ' ' | '\u{a0}'))
+            .map(|c| if c == ',' { '.' } else { c })
+            .collect(),
+        Some(_) => text
+            .chars()
+            .filter(|c| !matches!(c, ',' | ' ' | '\u{a0}'))
+            .collect(),
+        None => text.to_string(),
+    };
+
+    cleaned.parse::<f64>().ok().map(|_| cleaned)
+}
+
+impl DynamicXBRLContent {
+    /// Every element that carries a `contextRef`, in document order.
+    pub fn facts(&self) -> Vec<&XBRLElement> {
+        fn collect<'a>(elements: &'a [XBRLElement], out: &mut Vec<&'a XBRLElement>) {
+            for element in elements {
+                if element.context_ref.is_some() {
+                    out.push(element);
+                }
+                collect(&element.children, out);
+            }
+        }
+
+        let mut facts = Vec::new();
+        collect(&self.elements, &mut facts);
+        facts
+    }
+
+    /// Facts whose context places them on `member` of `dimension`, e.g. the
+    /// net sales of one reportable segment. Facts reported for the
+    /// dimension's default member carry no member for it and are not
+    /// returned.
+    pub fn facts_for_member(&self, dimension: &str, member: &str) -> Vec<&XBRLElement> {
+        self.facts()
+            .into_iter()
+            .filter(|fact| {
+                fact.context_ref.as_ref()
+                    .and_then(|c| self.contexts.get(c))
+                    .is_some_and(|c| c.dimensions().any(|d| d.dimension == dimension && d.member == member))
+            })
+            .collect()
+    }
+
+    /// The reporting period of the filing: the longest dimensionless duration
+    /// ending on the latest end date (e.g. the year-to-date period in a
+    /// quarterly report rather than the three-month quarter).
+    pub fn current_duration(&self) -> Option<Period> {
+        self.contexts
+            .values()
+            .filter(|c| !c.has_dimensions())
+            .filter_map(|c| match c.period {
+                Period::Duration { start, end } => Some((end, start)),
+                _ => None,
+            })
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(end, start)| Period::Duration { start, end })
+    }
+
+    /// The period of a context, e.g. the `CurrentYearDuration` a form
+    /// reports its figures for.
+    pub fn period_of(&self, context_ref: &str) -> Option<Period> {
+        self.contexts.get(context_ref).map(|c| c.period)
+    }
+
+    /// Periods of the consolidated and of the non-consolidated statements.
+    /// Filers without subsidiaries tag every context, comparatives included,
+    /// with the non-consolidated member.
+    fn statement_periods(&self) -> impl Iterator<Item = Period> + '_ {
+        self.contexts
+            .values()
+            .filter(|c| c.is_statement_context(true) || c.is_statement_context(false))
+            .map(|c| c.period)
+    }
+
+    /// The reporting period `current` followed by its comparatives: every
+    /// statement duration up to it that is about as long (e.g. the prior
+    /// year's year-to-date period, but not the three-month quarter), latest
+    /// first.
+    pub fn reporting_durations(&self, current: Period) -> Vec<Period> {
+        let Period::Duration { start, end } = current else {
+            return Vec::new();
+        };
+        let length = (end - start).num_days();
+
+        let mut periods: Vec<Period> = self.statement_periods()
+            .filter(|p| match p {
+                // Allows for leap years and 52/53-week fiscal years.
+                Period::Duration { start: s, end: e } => *e <= end && ((*e - *s).num_days() - length).abs() <= 7,
+                _ => false,
+            })
+            .collect();
+        periods.sort_by_key(|p| (std::cmp::Reverse(p.end_date()), p.start_date()));
+        periods.dedup();
+        periods
+    }
+
+    /// The columns of the balance sheet: the balance sheet date `current`
+    /// and the prior year-end it is compared with, latest first. Other
+    /// instants (prior quarter-ends, ...) only carry notes.
+    pub fn balance_sheet_instants(&self, current: NaiveDate, prior_year_end: Option<NaiveDate>) -> Vec<Period> {
+        let mut periods: Vec<Period> = self.statement_periods()
+            .filter(|p| matches!(p, Period::Instant(date) if *date == current || Some(*date) == prior_year_end))
+            .collect();
+        periods.sort_by_key(|p| std::cmp::Reverse(p.end_date()));
+        periods.dedup();
+        periods
+    }
+
+    /// Whether `context_ref` points at a context of the consolidated or
+    /// non-consolidated statements for `period`.
+    pub fn is_statement_context(&self, context_ref: &str, period: &Period, consolidated: bool) -> bool {
+        self.contexts
+            .get(context_ref)
+            .is_some_and(|c| c.is_statement_context(consolidated) && c.period == *period)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fact(value: &str, attributes: &[(&str, &str)]) -> XBRLElement {
+        XBRLElement {
+            name: "jppfs_cor:NetSales".to_string(),
+            value: Some(value.to_string()),
+            attributes: attributes.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            children: Vec::new(),
+            context_ref: Some("CurrentYearDuration".to_string()),
+            unit_ref: Some("JPY".to_string()),
+            unit: None,
+        }
+    }
+
+    #[test]
+    fn normalizes_displayed_numbers() {
+        assert_eq!(normalize_number(" 1,234,567 ", Some("ixt:numdotdecimal")).as_deref(), Some("1234567"));
+        assert_eq!(normalize_number("1.234,5", Some("ixt:numcommadecimal")).as_deref(), Some("1234.5"));
+        assert_eq!(normalize_number("－", Some("ixt:zerodash")).as_deref(), Some("0"));
+        assert_eq!(normalize_number("12.5", None).as_deref(), Some("12.5"));
+        assert_eq!(normalize_number("1,234", None), None);
+        assert_eq!(normalize_number("△", Some("ixt:numdotdecimal")), None);
+    }
+
+    #[test]
+    fn parses_decimals() {
+        assert_eq!(Decimals::parse("INF"), Some(Decimals::Infinite));
+        assert_eq!(Decimals::parse(" -6 "), Some(Decimals::Finite(-6)));
+        assert_eq!(Decimals::parse("2"), Some(Decimals::Finite(2)));
+        assert_eq!(Decimals::parse(""), None);
+    }
+
+    #[test]
+    fn applies_scale_and_sign() {
+        let loss = fact("1,234", &[("format", "ixt:numdotdecimal"), ("scale", "6"), ("sign", "-"), ("decimals", "-6")]);
+        assert_eq!(loss.numeric_value(), Some(NumericFact {
+            value: Some(-1_234_000_000.0),
+            decimals: Some(Decimals::Finite(-6)),
+            nil: false,
+        }));
+
+        let ratio = fact("17.55", &[("format", "ixt:numdotdecimal"), ("scale", "-2"), ("decimals", "4")]);
+        assert_eq!(ratio.numeric_value().and_then(|n| n.value), Some(0.1755));
+    }
+
+    #[test]
+    fn keeps_infinite_precision() {
+        let shares = fact("29631000", &[("decimals", "INF")]);
+        assert_eq!(shares.numeric_value(), Some(NumericFact {
+            value: Some(29631000.0),
+            decimals: Some(Decimals::Infinite),
+            nil: false,
+        }));
+    }
+
+    #[test]
+    fn reads_nil_facts_without_a_value() {
+        let nil = fact("", &[("xsi:nil", "true"), ("decimals", "-3")]);
+        assert!(nil.is_nil());
+        assert_eq!(nil.numeric_value(), Some(NumericFact {
+            value: None,
+            decimals: Some(Decimals::Finite(-3)),
+            nil: true,
+        }));
+    }
+
+    #[test]
+    fn skips_non_numeric_facts() {
+        let mut text = fact("株式会社サンプル", &[]);
+        text.unit_ref = None;
+        assert_eq!(text.numeric_value(), None);
+    }
+}
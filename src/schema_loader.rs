@@ -0,0 +1,178 @@
+// schema_loader.rs
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::xbrl::XBRLElement;
+use crate::models::taxonomy::{Balance, ConceptDefinition, PeriodType, Taxonomy};
+use crate::xbrl_archive::{XBRLArchive, DocumentKind, EntryKind};
+use crate::xbrl_parser::{parse_dynamic_xbrl, local_name};
+
+const LABEL_LINKBASE_REF_ROLE: &str = "http://www.xbrl.org/2003/role/labelLinkbaseRef";
+
+/// The concepts declared by one schema, the locations of the schemas it
+/// imports and of the label linkbases it references.
+struct ParsedSchema {
+    concepts: Vec<ConceptDefinition>,
+    imports: Vec<String>,
+    label_linkbases: Vec<String>,
+}
+
+/// Looks an attribute up by local name, whatever prefix the schema binds
+/// (`xbrli:periodType`, `xbrldt:...`).
+fn attribute<'a>(element: &'a XBRLElement, name: &str) -> Option<&'a str> {
+    element.attributes.iter()
+        .find(|(key, _)| local_name(key) == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse_schema(content: &str) -> Result<ParsedSchema, Box<dyn Error>> {
+    let document = parse_dynamic_xbrl(content)?;
+    let schema = document.elements.iter()
+        .find(|e| local_name(&e.name) == "schema")
+        .ok_or("No xsd:schema element found")?;
+
+    let prefix = schema.attributes.get("targetNamespace")
+        .and_then(|target| document.namespaces.iter().find(|(_, uri)| *uri == target))
+        .map(|(prefix, _)| prefix.clone());
+
+    let mut concepts = Vec::new();
+    let mut imports = Vec::new();
+    let mut label_linkbases = Vec::new();
+
+    for child in &schema.children {
+        match local_name(&child.name) {
+            "annotation" => {
+                let refs = child.children.iter()
+                    .filter(|c| local_name(&c.name) == "appinfo")
+                    .flat_map(|appinfo| &appinfo.children)
+                    .filter(|c| local_name(&c.name) == "linkbaseRef")
+                    .filter(|c| c.attributes.get("xlink:role").is_some_and(|r| r == LABEL_LINKBASE_REF_ROLE));
+                label_linkbases.extend(refs.filter_map(|c| c.attributes.get("xlink:href").cloned()));
+            }
+            "import" | "include" => {
+                if let Some(location) = child.attributes.get("schemaLocation") {
+                    imports.push(location.clone());
+                }
+            }
+            "element" => {
+                let Some(name) = child.attributes.get("name") else {
+                    continue;
+                };
+                // Only XBRL concepts carry a period type.
+                let Some(period_type) = attribute(child, "periodType") else {
+                    continue;
+                };
+
+                let concept = match &prefix {
+                    Some(prefix) => format!("{}:{}", prefix, name),
+                    None => match child.attributes.get("id").and_then(|id| id.rsplit_once('_')) {
+                        Some((prefix, name)) => format!("{}:{}", prefix, name),
+                        None => name.clone(),
+                    },
+                };
+
+                concepts.push(ConceptDefinition {
+                    concept,
+                    period_type: PeriodType::parse(period_type),
+                    balance: attribute(child, "balance").and_then(Balance::parse),
+                    data_type: child.attributes.get("type").cloned(),
+                    substitution_group: child.attributes.get("substitutionGroup").cloned(),
+                    is_abstract: child.attributes.get("abstract").is_some_and(|a| a == "true"),
+                    nillable: child.attributes.get("nillable").is_some_and(|n| n == "true"),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ParsedSchema { concepts, imports, label_linkbases })
+}
+
+/// Resolves a `schemaLocation` against the URL of the schema importing it.
+fn resolve_location(base: &str, location: &str) -> String {
+    if location.contains("://") {
+        return location.to_string();
+    }
+    match base.rfind('/') {
+        Some(i) => format!("{}/{}", &base[..i], location),
+        None => location.to_string(),
+    }
+}
+
+/// Finds a published schema or linkbase in the local taxonomy cache, which
+/// may keep files either under their host
+/// (`disclosure.edinet-fsa.go.jp/taxonomy/...`) or, like the EDINET taxonomy
+/// download, from the path onwards (`taxonomy/jppfs/...`).
+fn cached_path(cache_dir: &Path, location: &str) -> Option<PathBuf> {
+    let path = location.split_once("://").map_or(location, |(_, rest)| rest);
+    let without_host = path.split_once('/').map_or(path, |(_, rest)| rest);
+
+    [cache_dir.join(path), cache_dir.join(without_host)]
+        .into_iter()
+        .find(|p| p.is_file())
+}
+
+/// Where the EDINET taxonomy keeps the labels of a core schema that does not
+/// reference them: `jppfs_cor_2013-08-31.xsd` is labelled by
+/// `label/jppfs_2013-08-31_lab.xml` and `label/jppfs_2013-08-31_lab-en.xml`.
+fn conventional_label_linkbases(location: &str) -> Vec<String> {
+    let file = location.rsplit('/').next().unwrap_or(location);
+    let Some((prefix, version)) = file.strip_suffix(".xsd").and_then(|s| s.split_once("_cor_")) else {
+        return Vec::new();
+    };
+    ["lab", "lab-en"].iter()
+        .map(|suffix| format!("label/{}_{}_{}.xml", prefix, version, suffix))
+        .collect()
+}
+
+/// Loads the concept declarations of a filing's extension schema, then of
+/// every standard taxonomy schema it imports (directly or transitively) that
+/// is available in `cache_dir`, and locates the cached label linkbases of
+/// those standard schemas. Files missing from the cache are skipped.
+pub fn load_taxonomy(archive: &mut XBRLArchive, kind: DocumentKind, cache_dir: &Path) -> Result<Taxonomy, Box<dyn Error>> {
+    let mut taxonomy = Taxonomy::default();
+    let mut pending = Vec::new();
+
+    for content in archive.read_entries(kind, &[EntryKind::Schema])? {
+        let schema = parse_schema(&content)?;
+        for concept in schema.concepts {
+            taxonomy.concepts.insert(concept.concept.clone(), concept);
+        }
+        pending.extend(schema.imports.into_iter().filter(|l| l.contains("://")));
+    }
+
+    let mut visited = HashSet::new();
+    while let Some(location) = pending.pop() {
+        if !visited.insert(location.clone()) {
+            continue;
+        }
+        let Some(path) = cached_path(cache_dir, &location) else {
+            continue;
+        };
+        let Ok(schema) = fs::read_to_string(&path).map_err(Into::into).and_then(|c| parse_schema(&c)) else {
+            eprintln!("Skipping unreadable schema {}", path.display());
+            continue;
+        };
+
+        for concept in schema.concepts {
+            taxonomy.concepts.entry(concept.concept.clone()).or_insert(concept);
+        }
+        let label_linkbases = if schema.label_linkbases.is_empty() {
+            conventional_label_linkbases(&location)
+        } else {
+            schema.label_linkbases.clone()
+        };
+        for href in &label_linkbases {
+            if let Some(path) = cached_path(cache_dir, &resolve_location(&location, href)) {
+                if !taxonomy.label_linkbases.contains(&path) {
+                    taxonomy.label_linkbases.push(path);
+                }
+            }
+        }
+        pending.extend(schema.imports.iter().map(|i| resolve_location(&location, i)));
+    }
+
+    Ok(taxonomy)
+}
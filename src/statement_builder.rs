@@ -0,0 +1,209 @@
+// statement_builder.rs
+use std::collections::HashMap;
+use chrono::Duration;
+
+use crate::models::xbrl::{DynamicXBRLContent, Period};
+use crate::models::linkbase::{LabelLinkbase, Network};
+use crate::models::taxonomy::{PeriodType, Taxonomy};
+use crate::models::financial_statements::{PresentedStatement, StatementKind, StatementLineItem};
+use crate::xbrl_parser::local_name;
+
+fn statement_kind_for_role(role: &str) -> Option<StatementKind> {
+    let name = role.rsplit('/').next()?;
+    if !(role.contains("/role/jppfs/") || role.contains("/role/jpigp/")) {
+        return None;
+    }
+
+    if name.contains("BalanceSheet") || name.contains("StatementOfFinancialPosition") {
+        Some(StatementKind::BalanceSheet)
+    } else if name.contains("StatementOfComprehensiveIncome") {
+        Some(StatementKind::ComprehensiveIncome)
+    } else if name.contains("StatementOfIncome") || name.contains("StatementOfProfitOrLoss") {
+        Some(StatementKind::IncomeStatement)
+    } else if name.contains("StatementOfCashFlows") {
+        Some(StatementKind::CashFlows)
+    } else if name.contains("StatementOfChangesInEquity") || name.contains("StatementOfChangesInNetAssets") {
+        Some(StatementKind::ChangesInEquity)
+    } else {
+        None
+    }
+}
+
+/// Whether an extended link role holds consolidated statements, e.g.
+/// `rol_QuarterlyConsolidatedBalanceSheet` rather than
+/// `rol_QuarterlyBalanceSheet`.
+pub fn is_consolidated_role(role: &str) -> bool {
+    let name = role.rsplit('/').next().unwrap_or(role);
+    name.contains("Consolidated") && !name.contains("NonConsolidated")
+}
+
+/// Hypercube scaffolding in presentation trees (tables, axes and their
+/// members) that is not a line item of the statement.
+fn is_dimensional_scaffolding(concept: &str) -> bool {
+    let name = local_name(concept);
+    name.ends_with("Table") || name.ends_with("Axis") || name.ends_with("Member") || name.ends_with("Domain")
+}
+
+/// The numeric values of the consolidated or non-consolidated statements by
+/// concept and period.
+fn statement_values(xbrl: &DynamicXBRLContent, consolidated: bool) -> HashMap<(&str, Period), f64> {
+    let mut values = HashMap::new();
+    for fact in xbrl.facts() {
+        let Some(context) = fact.context_ref.as_ref().and_then(|c| xbrl.contexts.get(c)) else {
+            continue;
+        };
+        if let (true, Some(value)) = (context.is_statement_context(consolidated), fact.numeric_value().and_then(|f| f.value)) {
+            values.insert((fact.name.as_str(), context.period), value);
+        }
+    }
+    values
+}
+
+/// The periods a statement column reads its values for: the duration of
+/// flows and the balances at its start and end.
+#[derive(Clone, Copy)]
+struct Column {
+    duration: Period,
+    opening: Period,
+    closing: Period,
+}
+
+impl Column {
+    fn of(duration: Period) -> Option<Self> {
+        let Period::Duration { start, end } = duration else {
+            return None;
+        };
+        Some(Column {
+            duration,
+            opening: Period::Instant(start - Duration::days(1)),
+            closing: Period::Instant(end),
+        })
+    }
+}
+
+/// Rebuilds the primary financial statements from the presentation linkbase,
+/// attaching the value of each line item for the current period and for the
+/// prior one, as the filed statements show them side by side. Instant
+/// concepts take the closing balance (or the opening one for period start
+/// labels); concepts missing from the taxonomy take whichever period they
+/// have. The prior column of a balance sheet is the prior year-end, which is
+/// where the current year-to-date period opens.
+pub fn build_presented_statements(
+    xbrl: &DynamicXBRLContent,
+    presentation: &[Network],
+    labels: &LabelLinkbase,
+    taxonomy: &Taxonomy,
+) -> Vec<PresentedStatement> {
+    let Some(current) = xbrl.current_duration().and_then(Column::of) else {
+        return Vec::new();
+    };
+    let prior_period = xbrl.reporting_durations(current.duration)
+        .get(1)
+        .and_then(|&duration| Column::of(duration));
+
+    let scope_values: HashMap<bool, HashMap<(&str, Period), f64>> = [true, false]
+        .into_iter()
+        .map(|consolidated| (consolidated, statement_values(xbrl, consolidated)))
+        .collect();
+
+    let mut statements = Vec::new();
+
+    for network in presentation {
+        let Some(kind) = statement_kind_for_role(&network.role) else {
+            continue;
+        };
+        let consolidated = is_consolidated_role(&network.role);
+        let prior = match kind {
+            StatementKind::BalanceSheet => prior_period.map(|column| Column { closing: current.opening, ..column }),
+            _ => prior_period,
+        };
+
+        let values = &scope_values[&consolidated];
+
+        let mut statement = PresentedStatement {
+            role: network.role.clone(),
+            kind,
+            consolidated,
+            line_items: Vec::new(),
+        };
+
+        fn visit(
+            network: &Network,
+            concept: &str,
+            preferred_label: Option<&str>,
+            depth: usize,
+            path: &mut Vec<String>,
+            line_item: &dyn Fn(&str, Option<&str>, usize) -> StatementLineItem,
+            out: &mut Vec<StatementLineItem>,
+        ) {
+            if is_dimensional_scaffolding(concept) || path.iter().any(|c| c == concept) {
+                return;
+            }
+
+            out.push(line_item(concept, preferred_label, depth));
+
+            path.push(concept.to_string());
+            for child in network.children(concept) {
+                visit(network, &child.to, child.preferred_label.as_deref(), depth + 1, path, line_item, out);
+            }
+            path.pop();
+        }
+
+        let value = |concept: &str, preferred_label: Option<&str>, column: Column| {
+            let period = match preferred_label {
+                Some(role) if role.ends_with("periodStartLabel") => column.opening,
+                _ => match taxonomy.period_type(concept) {
+                    Some(PeriodType::Instant) => column.closing,
+                    Some(PeriodType::Duration) => column.duration,
+                    None if values.contains_key(&(concept, column.duration)) => column.duration,
+                    None => column.closing,
+                },
+            };
+            values.get(&(concept, period)).copied()
+        };
+
+        let line_item = |concept: &str, preferred_label: Option<&str>, depth: usize| {
+            StatementLineItem {
+                concept: concept.to_string(),
+                depth,
+                preferred_label: preferred_label.map(str::to_string),
+                label: labels.preferred_label(concept, "ja", preferred_label).map(str::to_string),
+                value: value(concept, preferred_label, current),
+                prior_value: prior.and_then(|prior| value(concept, preferred_label, prior)),
+            }
+        };
+
+        for root in network.roots() {
+            visit(network, root, None, 0, &mut Vec::new(), &line_item, &mut statement.line_items);
+        }
+
+        statements.push(statement);
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROLE_PREFIX: &str = "http://disclosure.edinet-fsa.go.jp/role/jppfs/";
+
+    #[test]
+    fn reads_statement_kind_and_scope_from_roles() {
+        let role = |name: &str| format!("{}{}", ROLE_PREFIX, name);
+
+        assert_eq!(statement_kind_for_role(&role("rol_QuarterlyConsolidatedBalanceSheet")), Some(StatementKind::BalanceSheet));
+        assert!(is_consolidated_role(&role("rol_QuarterlyConsolidatedBalanceSheet")));
+
+        assert_eq!(statement_kind_for_role(&role("rol_StatementOfIncome")), Some(StatementKind::IncomeStatement));
+        assert!(!is_consolidated_role(&role("rol_StatementOfIncome")));
+
+        assert_eq!(
+            statement_kind_for_role(&role("rol_ConsolidatedStatementOfComprehensiveIncome")),
+            Some(StatementKind::ComprehensiveIncome),
+        );
+        assert!(!is_consolidated_role(&role("rol_NonConsolidatedStatementOfCashFlows")));
+        assert_eq!(statement_kind_for_role("http://disclosure.edinet-fsa.go.jp/role/jpcrp/rol_CabinetOfficeOrdinance"), None);
+    }
+}
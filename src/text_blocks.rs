@@ -0,0 +1,150 @@
+// text_blocks.rs
+use crate::models::xbrl::{DynamicXBRLContent, TextBlock};
+use crate::models::taxonomy::Taxonomy;
+use crate::xbrl_parser::local_name;
+
+/// Elements whose end starts a new line of the plain text.
+const BLOCK_TAGS: [&str; 14] = ["p", "div", "br", "tr", "li", "table", "h1", "h2", "h3", "h4", "h5", "h6", "ul", "ol"];
+
+/// Whether a concept is a text block: typed `textBlockItemType` in the
+/// taxonomy, or, for concepts missing from it, named `...TextBlock` as every
+/// EDINET text block is.
+fn is_text_block(taxonomy: &Taxonomy, concept: &str) -> bool {
+    match taxonomy.concept(concept).and_then(|c| c.data_type.as_deref()) {
+        Some(data_type) => local_name(data_type) == "textBlockItemType",
+        None => concept.ends_with("TextBlock"),
+    }
+}
+
+/// Decodes the character references left in HTML text: the XML ones,
+/// numeric ones and `&nbsp;`. Unknown references are kept as written.
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        let decoded = rest.find(';').and_then(|end| {
+            let name = &rest[1..end];
+            let c = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => name.strip_prefix("#x").or_else(|| name.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| name.strip_prefix('#').map(|dec| dec.parse::<u32>()))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Reduces a text block's HTML to searchable plain text: tags are dropped,
+/// block elements end lines, table cells are separated by spaces, and runs
+/// of whitespace and blank lines are collapsed.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+
+        let tag = rest[open + 1..open + close].trim_start_matches('/');
+        let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default();
+        let name = local_name(name).to_ascii_lowercase();
+        if BLOCK_TAGS.contains(&name.as_str()) {
+            text.push('\n');
+        } else if name == "td" || name == "th" {
+            text.push(' ');
+        }
+
+        rest = &rest[open + close + 1..];
+    }
+    text.push_str(rest);
+
+    decode_entities(&text)
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Collects every text block fact of a filing with its HTML and plain text.
+/// The same block shown twice in a filing is kept once.
+pub fn extract_text_blocks(xbrl: &DynamicXBRLContent, taxonomy: &Taxonomy) -> Vec<TextBlock> {
+    let mut blocks: Vec<TextBlock> = Vec::new();
+
+    for fact in xbrl.facts() {
+        let (Some(context_ref), Some(html)) = (fact.context_ref.as_ref(), fact.value.as_ref()) else {
+            continue;
+        };
+        if fact.is_nil() || !is_text_block(taxonomy, &fact.name) {
+            continue;
+        }
+        if blocks.iter().any(|b| b.concept == fact.name && b.context_ref == *context_ref) {
+            continue;
+        }
+
+        blocks.push(TextBlock {
+            concept: fact.name.clone(),
+            context_ref: context_ref.clone(),
+            text: strip_html(html),
+            html: html.clone(),
+        });
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(decode_entities("A &amp; B &lt;C&gt; &quot;d&quot; &apos;e&apos;"), "A & B <C> \"d\" 'e'");
+        assert_eq!(decode_entities("&#x3042;&#12354;&nbsp;x"), "ああ x");
+        assert_eq!(decode_entities("&copy; & &#xZZ; &"), "&copy; & &#xZZ; &");
+    }
+
+    #[test]
+    fn strips_html_to_lines() {
+        let html = concat!(
+            r#"<div class="risk"><h3>事業等のリスク</h3><p>為替 &amp; 金利の   変動</p>"#,
+            "<table><tr><th>区分</th><td>1,000</td></tr><tr><td>合計</td><td>2,000</td></tr></table>",
+            "<p></p><br/>以上</div>",
+        );
+        assert_eq!(strip_html(html), "事業等のリスク\n為替 & 金利の 変動\n区分 1,000\n合計 2,000\n以上");
+    }
+
+    #[test]
+    fn keeps_text_after_an_unclosed_tag() {
+        assert_eq!(strip_html("本文<p"), "本文<p");
+    }
+}
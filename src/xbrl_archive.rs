@@ -0,0 +1,221 @@
+// xbrl_archive.rs
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+use crate::models::xbrl::XBRLElement;
+use crate::xbrl_parser::{parse_dynamic_xbrl, local_name};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Public,
+    Audit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Manifest,
+    Instance,
+    InlineXBRL,
+    Schema,
+    LabelLinkbase,
+    EnglishLabelLinkbase,
+    PresentationLinkbase,
+    CalculationLinkbase,
+    DefinitionLinkbase,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub kind: EntryKind,
+}
+
+/// One document of a filing (`XBRL/PublicDoc` or `XBRL/AuditDoc`) with its
+/// instance and inline XBRL pages as listed in its manifest.
+#[derive(Debug, Clone)]
+pub struct DocumentSet {
+    pub kind: DocumentKind,
+    pub instance: Option<String>,
+    pub ixbrl: Vec<String>,
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// An EDINET XBRL zip with every entry classified by document and file type.
+pub struct XBRLArchive {
+    archive: ZipArchive<File>,
+    pub documents: Vec<DocumentSet>,
+}
+
+impl DocumentSet {
+    pub fn entries_of(&self, kind: EntryKind) -> impl Iterator<Item = &str> {
+        self.entries.iter()
+            .filter(move |e| e.kind == kind)
+            .map(|e| e.path.as_str())
+    }
+}
+
+impl XBRLArchive {
+    pub fn open(zip_path: &Path) -> Result<Self, Box<dyn Error>> {
+        let file = File::open(zip_path)?;
+        let archive = ZipArchive::new(file)?;
+
+        let mut xbrl_archive = Self {
+            archive,
+            documents: Vec::new(),
+        };
+
+        for kind in [DocumentKind::Public, DocumentKind::Audit] {
+            let entries: Vec<ArchiveEntry> = xbrl_archive.archive.file_names()
+                .filter(|name| document_kind_of(name) == Some(kind))
+                .map(|name| ArchiveEntry {
+                    path: name.to_string(),
+                    kind: classify_entry(name),
+                })
+                .collect();
+
+            if entries.is_empty() {
+                continue;
+            }
+
+            let document = xbrl_archive.read_document_set(kind, entries)?;
+            xbrl_archive.documents.push(document);
+        }
+
+        Ok(xbrl_archive)
+    }
+
+    pub fn document(&self, kind: DocumentKind) -> Option<&DocumentSet> {
+        self.documents.iter().find(|d| d.kind == kind)
+    }
+
+    pub fn read_to_string(&mut self, path: &str) -> Result<String, Box<dyn Error>> {
+        let mut file = self.archive.by_name(path)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        Ok(content)
+    }
+
+    pub fn read_instance(&mut self, kind: DocumentKind) -> Result<String, Box<dyn Error>> {
+        let path = self.document(kind)
+            .and_then(|d| d.instance.clone())
+            .ok_or("No instance document found in the zip archive")?;
+        self.read_to_string(&path)
+    }
+
+    /// Reads every entry of the given kinds (e.g. both label linkbases) of a
+    /// document.
+    pub fn read_entries(&mut self, kind: DocumentKind, entry_kinds: &[EntryKind]) -> Result<Vec<String>, Box<dyn Error>> {
+        let paths: Vec<String> = self.document(kind)
+            .map(|d| {
+                entry_kinds.iter()
+                    .flat_map(|k| d.entries_of(*k))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        paths.iter().map(|p| self.read_to_string(p)).collect()
+    }
+
+    /// Reads the inline XBRL pages of a document in manifest order.
+    pub fn read_ixbrl(&mut self, kind: DocumentKind) -> Result<Vec<String>, Box<dyn Error>> {
+        let paths = self.document(kind)
+            .map(|d| d.ixbrl.clone())
+            .unwrap_or_default();
+
+        if paths.is_empty() {
+            return Err("No inline XBRL pages found in the zip archive".into());
+        }
+
+        paths.iter().map(|p| self.read_to_string(p)).collect()
+    }
+
+    /// Builds a document set from its manifest, or from the classified
+    /// entries when the archive has no manifest for it.
+    fn read_document_set(&mut self, kind: DocumentKind, entries: Vec<ArchiveEntry>) -> Result<DocumentSet, Box<dyn Error>> {
+        let mut document = DocumentSet {
+            kind,
+            instance: None,
+            ixbrl: Vec::new(),
+            entries,
+        };
+
+        let manifest_path = document.entries_of(EntryKind::Manifest).next().map(str::to_string);
+        if let Some(manifest_path) = manifest_path {
+            let dir = &manifest_path[..manifest_path.rfind('/').map_or(0, |i| i + 1)];
+            let manifest = parse_dynamic_xbrl(&self.read_to_string(&manifest_path)?)?;
+
+            if let Some(instance) = find_manifest_instance(&manifest.elements) {
+                document.instance = instance.attributes.get("preferredFilename")
+                    .map(|f| format!("{}{}", dir, f));
+                document.ixbrl = instance.children.iter()
+                    .filter(|c| local_name(&c.name) == "ixbrl")
+                    .filter_map(|c| c.value.as_ref())
+                    .map(|f| format!("{}{}", dir, f.trim()))
+                    .collect();
+            }
+        }
+
+        if document.instance.is_none() {
+            let instance = document.entries_of(EntryKind::Instance).next().map(str::to_string);
+            document.instance = instance;
+        }
+        if document.ixbrl.is_empty() {
+            let mut pages: Vec<String> = document.entries_of(EntryKind::InlineXBRL).map(str::to_string).collect();
+            pages.sort();
+            document.ixbrl = pages;
+        }
+
+        Ok(document)
+    }
+}
+
+fn find_manifest_instance(elements: &[XBRLElement]) -> Option<&XBRLElement> {
+    elements.iter().find_map(|e| {
+        if local_name(&e.name) == "instance" {
+            Some(e)
+        } else {
+            find_manifest_instance(&e.children)
+        }
+    })
+}
+
+fn document_kind_of(path: &str) -> Option<DocumentKind> {
+    if path.contains("XBRL/PublicDoc/") {
+        Some(DocumentKind::Public)
+    } else if path.contains("XBRL/AuditDoc/") {
+        Some(DocumentKind::Audit)
+    } else {
+        None
+    }
+}
+
+fn classify_entry(path: &str) -> EntryKind {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+
+    if file_name.starts_with("manifest_") && file_name.ends_with(".xml") {
+        EntryKind::Manifest
+    } else if file_name.ends_with("_ixbrl.htm") {
+        EntryKind::InlineXBRL
+    } else if file_name.ends_with(".xbrl") {
+        EntryKind::Instance
+    } else if file_name.ends_with(".xsd") {
+        EntryKind::Schema
+    } else if file_name.ends_with("_lab.xml") {
+        EntryKind::LabelLinkbase
+    } else if file_name.ends_with("_lab-en.xml") {
+        EntryKind::EnglishLabelLinkbase
+    } else if file_name.ends_with("_pre.xml") {
+        EntryKind::PresentationLinkbase
+    } else if file_name.ends_with("_cal.xml") {
+        EntryKind::CalculationLinkbase
+    } else if file_name.ends_with("_def.xml") {
+        EntryKind::DefinitionLinkbase
+    } else {
+        EntryKind::Other
+    }
+}
@@ -0,0 +1,246 @@
+// xbrl_parser.rs
+use std::collections::HashMap;
+use std::error::Error;
+use chrono::NaiveDate;
+use quick_xml::Reader;
+use quick_xml::events::{BytesStart, Event};
+
+use crate::models::xbrl::{XBRLElement, DynamicXBRLContent, XBRLContext, EntityIdentifier, Period, DimensionMember, XBRLUnit};
+
+pub fn parse_dynamic_xbrl(content: &str) -> Result<DynamicXBRLContent, Box<dyn Error>> {
+    let mut reader = Reader::from_str(content);
+    reader.trim_text(true);
+
+    let mut namespaces = HashMap::new();
+    let mut contexts = HashMap::new();
+    let mut units = HashMap::new();
+    let mut elements = Vec::new();
+    let mut element_stack = Vec::new();
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                element_stack.push(start_element(&e, &mut namespaces)?);
+            },
+            Ok(Event::Empty(e)) => {
+                let element = start_element(&e, &mut namespaces)?;
+                if let Some(parent) = element_stack.last_mut() {
+                    parent.children.push(element);
+                } else {
+                    elements.push(element);
+                }
+            },
+            // An element's text may arrive in several events (split around
+            // comments and CDATA sections); all of it is kept, unescaped, so
+            // text blocks keep their full HTML.
+            Ok(Event::Text(e)) => {
+                if let Some(element) = element_stack.last_mut() {
+                    let text = e.unescape().map(|t| t.into_owned())
+                        .or_else(|_| String::from_utf8(e.to_vec()))?;
+                    element.value.get_or_insert_with(String::new).push_str(&text);
+                }
+            },
+            Ok(Event::CData(e)) => {
+                if let Some(element) = element_stack.last_mut() {
+                    let text = String::from_utf8(e.into_inner().to_vec())?;
+                    element.value.get_or_insert_with(String::new).push_str(&text);
+                }
+            },
+            Ok(Event::End(_)) => {
+                if let Some(element) = element_stack.pop() {
+                    match local_name(&element.name) {
+                        "context" => {
+                            if let Some(context) = parse_context(&element) {
+                                contexts.insert(context.id.clone(), context);
+                            }
+                        }
+                        "unit" => {
+                            if let Some(unit) = parse_unit(&element) {
+                                units.insert(unit.id.clone(), unit);
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    if element_stack.is_empty() {
+                        elements.push(element);
+                    } else if let Some(parent) = element_stack.last_mut() {
+                        parent.children.push(element);
+                    }
+                }
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Error parsing XBRL: {:?}", e).into()),
+            _ => (),
+        }
+    }
+
+    resolve_units(&mut elements, &units);
+
+    Ok(DynamicXBRLContent {
+        namespaces,
+        contexts,
+        units,
+        elements,
+    })
+}
+
+pub fn start_element(e: &BytesStart, namespaces: &mut HashMap<String, String>) -> Result<XBRLElement, Box<dyn Error>> {
+    let name = String::from_utf8(e.name().as_ref().to_vec())?;
+
+    let mut attributes = HashMap::new();
+    let mut context_ref = None;
+    let mut unit_ref = None;
+
+    for attr in e.attributes() {
+        let attr = attr?;
+        let key = String::from_utf8(attr.key.as_ref().to_vec())?;
+        let value = String::from_utf8(attr.value.to_vec())?;
+
+        match key.as_str() {
+            "contextRef" => context_ref = Some(value),
+            "unitRef" => unit_ref = Some(value),
+            _ => {
+                if let Some(prefix) = key.strip_prefix("xmlns:") {
+                    namespaces.insert(prefix.to_string(), value.clone());
+                }
+                attributes.insert(key, value);
+            }
+        }
+    }
+
+    Ok(XBRLElement {
+        name,
+        value: None,
+        attributes,
+        children: Vec::new(),
+        context_ref,
+        unit_ref,
+        unit: None,
+    })
+}
+
+pub fn local_name(qname: &str) -> &str {
+    qname.rsplit(':').next().unwrap_or(qname)
+}
+
+pub fn parse_xbrl_date(text: &str) -> Option<NaiveDate> {
+    let date = text.trim().get(..10)?;
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
+pub fn parse_context(element: &XBRLElement) -> Option<XBRLContext> {
+    let id = element.attributes.get("id")?.clone();
+
+    let mut entity = EntityIdentifier::default();
+    let mut period = None;
+    let mut segment = Vec::new();
+    let mut scenario = Vec::new();
+
+    fn parse_members(container: &XBRLElement) -> Vec<DimensionMember> {
+        container.children.iter()
+            .filter_map(|m| {
+                let dimension = m.attributes.get("dimension")?.clone();
+                match local_name(&m.name) {
+                    "explicitMember" => Some(DimensionMember {
+                        dimension,
+                        member: m.value.clone().unwrap_or_default().trim().to_string(),
+                        typed: false,
+                    }),
+                    "typedMember" => Some(DimensionMember {
+                        dimension,
+                        member: m.children.first()
+                            .and_then(|v| v.value.clone())
+                            .unwrap_or_default(),
+                        typed: true,
+                    }),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    for child in &element.children {
+        match local_name(&child.name) {
+            "entity" => {
+                for part in &child.children {
+                    match local_name(&part.name) {
+                        "identifier" => {
+                            entity.scheme = part.attributes.get("scheme").cloned().unwrap_or_default();
+                            entity.value = part.value.clone().unwrap_or_default();
+                        }
+                        "segment" => segment = parse_members(part),
+                        _ => {}
+                    }
+                }
+            }
+            "period" => {
+                let date_of = |name: &str| {
+                    child.children.iter()
+                        .find(|p| local_name(&p.name) == name)
+                        .and_then(|p| p.value.as_deref())
+                        .and_then(parse_xbrl_date)
+                };
+
+                period = if let Some(instant) = date_of("instant") {
+                    Some(Period::Instant(instant))
+                } else if let (Some(start), Some(end)) = (date_of("startDate"), date_of("endDate")) {
+                    Some(Period::Duration { start, end })
+                } else if child.children.iter().any(|p| local_name(&p.name) == "forever") {
+                    Some(Period::Forever)
+                } else {
+                    None
+                };
+            }
+            "scenario" => scenario = parse_members(child),
+            _ => {}
+        }
+    }
+
+    Some(XBRLContext {
+        id,
+        entity,
+        period: period?,
+        segment,
+        scenario,
+    })
+}
+
+pub fn parse_unit(element: &XBRLElement) -> Option<XBRLUnit> {
+    let id = element.attributes.get("id")?.clone();
+
+    fn measures_of(element: &XBRLElement) -> Vec<String> {
+        element.children.iter()
+            .filter(|m| local_name(&m.name) == "measure")
+            .filter_map(|m| m.value.as_ref().map(|v| v.trim().to_string()))
+            .collect()
+    }
+
+    let mut numerator = measures_of(element);
+    let mut denominator = Vec::new();
+
+    if let Some(divide) = element.children.iter().find(|c| local_name(&c.name) == "divide") {
+        for part in &divide.children {
+            match local_name(&part.name) {
+                "unitNumerator" => numerator = measures_of(part),
+                "unitDenominator" => denominator = measures_of(part),
+                _ => {}
+            }
+        }
+    }
+
+    Some(XBRLUnit {
+        id,
+        numerator,
+        denominator,
+    })
+}
+
+pub fn resolve_units(elements: &mut [XBRLElement], units: &HashMap<String, XBRLUnit>) {
+    for element in elements {
+        if let Some(unit_ref) = &element.unit_ref {
+            element.unit = units.get(unit_ref).cloned();
+        }
+        resolve_units(&mut element.children, units);
+    }
+}